@@ -5,6 +5,8 @@ bevy_embasset::assets!(
         GfxExplosion = "explosion.png",
         GfxFlame = "flame.png",
         GfxLaser = "laser.png",
+        GfxPickup = "pickup.png",
+        GfxSaucer = "saucer.png",
         GfxSpaceship = "spaceship.png",
         GfxTrophy = "trophy.png",
     },
@@ -53,6 +55,7 @@ bevy_embasset::assets!(
         AsteroidExplode = "asteroid_explode.wav",
         Laser = "laser.wav",
         Notification = "notification.wav",
+        Shield = "shield.wav",
         ShipExplode = "ship_explode.wav",
         Thruster = "thruster.wav",
     },
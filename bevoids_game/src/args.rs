@@ -0,0 +1,236 @@
+use derive_more::Display;
+use std::{ffi::OsString, path::PathBuf};
+
+use crate::bevoids::GameState;
+
+pub const USAGE: &str = "\
+Usage: bevoids [OPTIONS] [COMMAND]
+
+Commands:
+      export-highscores <FILE>  Export the highscores to FILE (.csv or .json), then exit
+      import-highscores <FILE>  Merge the highscores in FILE (.csv or .json) into the boards,
+                                skipping those already there, then exit
+
+Options:
+      --settings <FILE>         Load settings from FILE, on top of the users own settings
+      --watch-settings          Reload the settings files when they change
+      --window-size <WxH>       Override the window size, e.g. 1024x768
+      --start <STATE>           Start in STATE: main-menu, playing or highscores
+      --seed <SEED>             Seed the random number generator, making games reproducible
+      --log <FILTER>            Log filter, e.g. info,bevoids=debug
+      --highscores <FILE>       Read and write highscores from FILE
+      --print-default-settings  Print the settings in effect as JSON, then exit
+  -h, --help                    Print this help, then exit";
+
+/// Command line arguments of the game.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub settings: Option<PathBuf>,
+    pub watch_settings: bool,
+    pub window_size: Option<(u32, u32)>,
+    pub start_state: Option<GameState>,
+    pub seed: Option<u64>,
+    pub log_filter: Option<String>,
+    pub highscores: Option<PathBuf>,
+    pub print_default_settings: bool,
+    pub help: bool,
+    pub command: Option<Command>,
+}
+
+/// Something to do instead of playing.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    ExportHighscores(PathBuf),
+    ImportHighscores(PathBuf),
+}
+
+#[derive(Debug, Display, PartialEq)]
+pub enum ArgsError {
+    #[display(fmt = "unknown argument `{}`", _0)]
+    Unknown(String),
+    #[display(fmt = "`{}` requires a value", _0)]
+    MissingValue(&'static str),
+    #[display(fmt = "invalid value `{}` for `{}`: {}", value, arg, reason)]
+    InvalidValue {
+        arg: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+}
+
+impl std::error::Error for ArgsError {}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self, ArgsError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.to_string_lossy().into_owned();
+            match arg.as_str() {
+                "--settings" => {
+                    parsed.settings = Some(value(&mut args, "--settings")?.into());
+                }
+                "--watch-settings" => parsed.watch_settings = true,
+                "--window-size" => {
+                    let size = value(&mut args, "--window-size")?;
+                    parsed.window_size =
+                        Some(parse_window_size(&size).ok_or(ArgsError::InvalidValue {
+                            arg: "--window-size",
+                            value: size,
+                            reason: "expected WIDTHxHEIGHT",
+                        })?);
+                }
+                "--start" => {
+                    let state = value(&mut args, "--start")?;
+                    parsed.start_state = Some(match state.as_str() {
+                        "main-menu" => GameState::MainMenu,
+                        "playing" => GameState::Playing,
+                        "highscores" => GameState::HighScoreMenu,
+                        _ => {
+                            return Err(ArgsError::InvalidValue {
+                                arg: "--start",
+                                value: state,
+                                reason: "expected main-menu, playing or highscores",
+                            })
+                        }
+                    });
+                }
+                "--seed" => {
+                    let seed = value(&mut args, "--seed")?;
+                    parsed.seed = Some(seed.parse().map_err(|_| ArgsError::InvalidValue {
+                        arg: "--seed",
+                        value: seed,
+                        reason: "expected a positive number",
+                    })?);
+                }
+                "--log" => parsed.log_filter = Some(value(&mut args, "--log")?),
+                "--highscores" => {
+                    parsed.highscores = Some(value(&mut args, "--highscores")?.into());
+                }
+                "--print-default-settings" => parsed.print_default_settings = true,
+                "-h" | "--help" => parsed.help = true,
+                "export-highscores" => {
+                    let file = value(&mut args, "export-highscores")?;
+                    parsed.command = Some(Command::ExportHighscores(file.into()));
+                }
+                "import-highscores" => {
+                    let file = value(&mut args, "import-highscores")?;
+                    parsed.command = Some(Command::ImportHighscores(file.into()));
+                }
+                _ => return Err(ArgsError::Unknown(arg)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn value(
+    args: &mut impl Iterator<Item = OsString>,
+    arg: &'static str,
+) -> Result<String, ArgsError> {
+    args.next()
+        .map(|value| value.to_string_lossy().into_owned())
+        .ok_or(ArgsError::MissingValue(arg))
+}
+
+fn parse_window_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    match (width.parse().ok()?, height.parse().ok()?) {
+        (0, _) | (_, 0) => None,
+        size => Some(size),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, ArgsError> {
+        Args::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn no_arguments_are_defaults() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn all_arguments_are_parsed() {
+        let args = parse(&[
+            "--settings",
+            "my.json",
+            "--watch-settings",
+            "--window-size",
+            "1024x768",
+            "--start",
+            "playing",
+            "--seed",
+            "42",
+            "--log",
+            "debug",
+            "--highscores",
+            "scores.json",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            args,
+            Args {
+                settings: Some("my.json".into()),
+                watch_settings: true,
+                window_size: Some((1024, 768)),
+                start_state: Some(GameState::Playing),
+                seed: Some(42),
+                log_filter: Some("debug".into()),
+                highscores: Some("scores.json".into()),
+                ..Args::default()
+            }
+        );
+    }
+
+    #[test]
+    fn commands_are_parsed() {
+        let args = parse(&[
+            "--highscores",
+            "scores.json",
+            "export-highscores",
+            "out.csv",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::ExportHighscores("out.csv".into()))
+        );
+        assert_eq!(args.highscores, Some("scores.json".into()));
+
+        assert_eq!(
+            parse(&["import-highscores", "in.json"]).unwrap().command,
+            Some(Command::ImportHighscores("in.json".into()))
+        );
+        assert_eq!(
+            parse(&["import-highscores"]),
+            Err(ArgsError::MissingValue("import-highscores"))
+        );
+    }
+
+    #[test]
+    fn invalid_arguments_are_errors() {
+        assert_eq!(
+            parse(&["--bogus"]),
+            Err(ArgsError::Unknown("--bogus".into()))
+        );
+        assert_eq!(parse(&["--seed"]), Err(ArgsError::MissingValue("--seed")));
+        assert!(matches!(
+            parse(&["--window-size", "1024"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["--window-size", "0x768"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["--start", "paused"]),
+            Err(ArgsError::InvalidValue { .. })
+        ));
+    }
+}
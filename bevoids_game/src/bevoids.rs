@@ -1,35 +1,64 @@
 use bevoids_assets::{BackgroundAsset, EnumCount, SoundAsset};
-use bevy::{core::FixedTimestep, ecs::{schedule::ShouldRun}, log, prelude::*};
+use bevy::{
+    core::FixedTimestep,
+    ecs::schedule::ShouldRun,
+    log,
+    prelude::*,
+    window::{WindowId, WindowResized},
+};
 use bevy_effects::{
     animation::{SpriteAnimationPlugin, SpriteAnimationEvent},
     despawn::{DespawnPlugin, FadeDespawn, FadeIn},
-    sound::SoundEffectsPlugin,
+    sound::{SetVolSfx, SfxCmdEvent, SoundEffectsPlugin},
 };
-use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_egui::{egui, EguiContext, EguiPlugin, EguiSystem};
 #[cfg(feature = "inspector")]
 use bevy_inspector_egui::WorldInspectorPlugin;
 use derive_more::Display;
 use rand::Rng;
+use serde_json::json;
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     bevoids::{
-        highscore::{load_highscores, update_score_system, AddScoreEvent, Score},
-        settings::Settings,
+        highscore::{
+            apply_highscore_outcomes_system, export_file, import_file, load_highscores,
+            refresh_highscores_system, update_score_system, AddScoreEvent, BoardCapacities,
+            HighScoreWarning, HighScoresFile, Score,
+        },
+        settings::{update_user_settings, BaseSettings, Settings},
     },
     bounds::{GfxBounds, WinBoundsPlugin},
 };
 
+mod achievement;
 mod asteroids;
+mod dirs;
+mod files;
 mod highscore;
 mod hit_test;
+mod hyperspace;
+mod input;
 mod laser;
 mod movement;
+mod pickup;
 mod player;
+mod profile;
 mod resources;
+mod rng;
+mod saucer;
 pub mod settings;
+mod shield;
+mod stats;
 mod ui;
 
-use {asteroids::*, hit_test::*, laser::*, movement::*, player::*, resources::*, ui::*};
+use {
+    achievement::*, asteroids::*, hit_test::*, hyperspace::*, input::*, laser::*, movement::*,
+    pickup::*, player::*, profile::*, resources::*, rng::*, saucer::*, shield::*, stats::*, ui::*,
+};
 
 #[derive(Debug, Display, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum GameState {
@@ -39,10 +68,22 @@ pub enum GameState {
     Paused,
     GameOver,
     NewHighScore,
+    Options,
+    KeyBindings,
+    Profiles,
+    Statistics,
+    Achievements,
 }
 
 #[derive(Debug, Default)]
-pub struct Bevoids {}
+pub struct Bevoids {
+    /// State to start in, instead of the main menu.
+    pub start_state: Option<GameState>,
+    /// Seed for the random number generator, random if not given.
+    pub seed: Option<u64>,
+    /// Location of the highscores, instead of the users data directory.
+    pub highscores: Option<PathBuf>,
+}
 
 impl Plugin for Bevoids {
     fn build(&self, app: &mut App) {
@@ -55,8 +96,14 @@ impl Plugin for Bevoids {
             .add_event::<SpawnAsteroidEvent>()
             .add_event::<AsteroidShotEvent>()
             .add_event::<AsteroidExplosionEvent>()
+            .add_event::<SaucerShotEvent>()
+            .add_event::<SaucerExplosionEvent>()
+            .add_event::<SpawnPickupEvent>()
+            .add_event::<PickupCollectedEvent>()
             .add_event::<FireLaserEvent>()
-            .add_event::<AddScoreEvent>();
+            .add_event::<LaserSpawnedEvent>()
+            .add_event::<AddScoreEvent>()
+            .add_event::<ProfileSettingsChangedEvent>();
 
         #[cfg(feature = "inspector")]
         app.add_plugin(WorldInspectorPlugin::new());
@@ -67,9 +114,26 @@ impl Plugin for Bevoids {
             .add_plugin(SoundEffectsPlugin::<SoundAsset>::default())
             .add_plugin(SpriteAnimationPlugin::default())
             .add_plugin(WinBoundsPlugin)
+            .add_plugin(InputActionPlugin)
             .add_startup_system(egui_defaults_system)
             .add_system(capture_cursor_when_playing_system)
             .add_system(esc_to_pause_unpause_system)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gamepad_menu_navigation_system
+                    .after(EguiSystem::ProcessInput)
+                    .before(EguiSystem::BeginFrame),
+            )
+            .add_system(display_highscore_warning_system)
+            .add_system(display_achievement_toasts_system)
+            .add_system(apply_highscore_outcomes_system)
+            .add_system(apply_difficulty_system)
+            .add_system(apply_volume_settings_system)
+            .add_system(apply_window_settings_system)
+            .add_system(toggle_fullscreen_system)
+            .add_system(window_resized_system)
+            .add_system(remember_window_size_system)
+            .add_system(remember_profile_settings_system)
             .add_system_set(
                 SystemSet::new()
                     .with_run_criteria(run_criteria_if_not_paused)
@@ -80,8 +144,32 @@ impl Plugin for Bevoids {
             .add_system(spawn_asteroid_event_system)
             .add_system(asteroid_explosion_system);
 
+        // resources
+        app.insert_resource(GameRng::new(self.seed))
+            .insert_resource(Profiles::load())
+            .init_resource::<AchievementToasts>();
+        match self
+            .highscores
+            .clone()
+            .map_or_else(HighScoresFile::locate, |path| Ok(HighScoresFile(path)))
+        {
+            Ok(file) => app.insert_resource(file),
+            Err(err) => {
+                log::warn!("highscores will not be saved: {}", err);
+                app.insert_resource(HighScoreWarning(format!(
+                    "Highscores will not be saved: {}",
+                    err
+                )))
+            }
+        };
+        let statistics = app
+            .world
+            .get_resource::<HighScoresFile>()
+            .map(HighScoresFile::statistics_file);
+        app.insert_resource(LifetimeStats::load(statistics));
+
         // introduce the state to its relevant stages
-        app.insert_resource(State::new(GameState::MainMenu))
+        app.insert_resource(State::new(self.start_state.unwrap_or(GameState::MainMenu)))
             .add_system_set_to_stage(CoreStage::PreUpdate, State::<GameState>::get_driver())
             .add_system_set_to_stage(CoreStage::Update, State::<GameState>::get_driver())
             .add_system_set_to_stage(CoreStage::PostUpdate, State::<GameState>::get_driver());
@@ -93,14 +181,49 @@ impl Plugin for Bevoids {
         setup_paused(app);
         setup_gameover(app);
         setup_new_highscore(app);
+        setup_options(app);
+        setup_keybindings(app);
+        setup_profiles(app);
+        setup_statistics(app);
+        setup_achievements(app);
     }
 }
 
+/// Exports the highscores kept in the `highscores` file - or the users data directory - to
+/// `path`, as CSV or JSON depending on its extension.
+///
+/// Returns the number of highscores exported.
+pub fn export_highscores(
+    highscores: Option<PathBuf>,
+    path: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    let file = highscores.map_or_else(HighScoresFile::locate, |path| Ok(HighScoresFile(path)))?;
+    Ok(export_file(&file, path)?)
+}
+
+/// Merges the highscores exported to `path` into those kept in the `highscores` file - or the
+/// users data directory - skipping those already there.
+///
+/// Returns the number of highscores that made it onto the boards.
+pub fn import_highscores(
+    highscores: Option<PathBuf>,
+    settings: &Settings,
+    path: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    let file = highscores.map_or_else(HighScoresFile::locate, |path| Ok(HighScoresFile(path)))?;
+    Ok(import_file(
+        &file,
+        &BoardCapacities::from(settings),
+        settings.general.unverified_highscores,
+        path,
+    )?)
+}
+
 fn setup_initialize(app: &mut App) {
-    app.add_startup_system(load_highscores)
+    app.add_startup_system(apply_active_profile_system)
+        .add_startup_system(load_highscores)
         .add_startup_system(define_animations)
         .add_startup_system(change_background_system)
-        .add_startup_system(spawn_menu_asteroids_system)
         .add_system_set(
             SystemSet::new()
                 .with_run_criteria(FixedTimestep::step(10.0))
@@ -117,8 +240,12 @@ fn setup_mainmenu(app: &mut App) {
 
 fn setup_highscore(app: &mut App) {
     let state = GameState::HighScoreMenu;
-    app.add_system_set(SystemSet::on_enter(state).with_system(spawn_menu_asteroids_system))
-        .add_system_set(SystemSet::on_update(state).with_system(display_highscore_menu_system));
+    app.add_system_set(
+        SystemSet::on_enter(state)
+            .with_system(spawn_menu_asteroids_system)
+            .with_system(refresh_highscores_system),
+    )
+    .add_system_set(SystemSet::on_update(state).with_system(display_highscore_menu_system));
 }
 
 fn setup_playing(app: &mut App) {
@@ -128,6 +255,13 @@ fn setup_playing(app: &mut App) {
         SystemSet::on_enter(state)
             .with_system(despawn_menu_asteroids_system)
             .with_system(prep_playingfield_system)
+            .with_system(reset_run_stats_system)
+            .with_system(count_game_played_system)
+            .with_system(reset_run_progress_system)
+            .with_system(reset_hyperspace_cooldown_system)
+            .with_system(reset_shield_system)
+            .with_system(reset_saucer_spawner_system)
+            .with_system(reset_power_ups_system)
             .with_system(spawn_player_system)
             .with_system(spawn_asteroid_spawner_system),
     )
@@ -135,17 +269,43 @@ fn setup_playing(app: &mut App) {
         SystemSet::on_update(state)
             .with_system(display_playing_ui_system)
             .with_system(asteroid_spawner_system)
+            .with_system(saucer_spawner_system)
+            .with_system(saucer_window_system)
+            .with_system(saucer_flight_system)
+            .with_system(saucer_fire_system)
+            .with_system(respawn_player_system)
+            .with_system(invulnerability_system)
             .with_system(player_controls_system.label("input"))
-            .with_system(laser_fired_system.after("input"))
+            .with_system(laser_fired_system.label("fire").after("input"))
+            .with_system(hyperspace_jump_system.after("input"))
+            .with_system(rapid_fire_system.after("input").before("fire"))
+            .with_system(power_ups_system)
+            .with_system(hyperspace_system)
+            .with_system(shield_system.after("input").before("hittest"))
             .with_system(laser_vs_asteroid_system.label("hittest"))
             .with_system(player_vs_asteroid_system.label("hittest"))
+            .with_system(laser_vs_saucer_system.label("hittest"))
+            .with_system(enemy_laser_vs_player_system.label("hittest"))
+            .with_system(player_vs_saucer_system.label("hittest"))
+            .with_system(player_vs_pickup_system.label("hittest"))
             .with_system(player_dead_system.after("hittest"))
             .with_system(update_score_system.after("hittest"))
-            .with_system(shot_asteroid_system.after("hittest")),
+            .with_system(update_run_stats_system.label("stats").after("hittest"))
+            .with_system(update_lifetime_stats_system.after("stats"))
+            .with_system(track_run_progress_system.label("progress").after("stats"))
+            .with_system(unlock_achievements_system.after("progress"))
+            .with_system(shot_asteroid_system.after("hittest"))
+            .with_system(saucer_shot_system.after("hittest"))
+            .with_system(saucer_explosion_system.after("hittest"))
+            .with_system(spawn_pickup_event_system.after("hittest"))
+            .with_system(collect_pickup_system.after("hittest")),
     )
+    .add_system_set(SystemSet::on_pause(state).with_system(save_lifetime_stats_system))
     .add_system_set(
         SystemSet::on_exit(state)
+            .with_system(save_lifetime_stats_system)
             .with_system(stop_thruster_sound_system)
+            .with_system(stop_shield_sound_system)
             .with_system(despawn_asteroid_spawner_system),
     );
 }
@@ -158,27 +318,64 @@ fn setup_paused(app: &mut App) {
 
 fn setup_gameover(app: &mut App) {
     let state = GameState::GameOver;
-    app.add_system_set(SystemSet::on_update(state).with_system(display_gameover_menu_system))
+    app.add_system_set(SystemSet::on_enter(state).with_system(record_personal_best_system))
+        .add_system_set(SystemSet::on_update(state).with_system(display_gameover_menu_system))
         .add_system_set(SystemSet::on_exit(state).with_system(clear_playingfield_system));
 }
 
 fn setup_new_highscore(app: &mut App) {
     let state = GameState::NewHighScore;
-    app.add_system_set(SystemSet::on_update(state).with_system(display_new_highscore_menu_system))
+    app.add_system_set(SystemSet::on_enter(state).with_system(record_personal_best_system))
+        .add_system_set(SystemSet::on_update(state).with_system(display_new_highscore_menu_system))
         .add_system_set(SystemSet::on_exit(state).with_system(clear_playingfield_system));
 }
 
+fn setup_options(app: &mut App) {
+    let state = GameState::Options;
+    app.add_system_set(SystemSet::on_update(state).with_system(display_options_menu_system))
+        .add_system_set(SystemSet::on_exit(state).with_system(save_options_system));
+}
+
+fn setup_keybindings(app: &mut App) {
+    let state = GameState::KeyBindings;
+    app.add_system_set(SystemSet::on_update(state).with_system(display_keybindings_menu_system))
+        .add_system_set(SystemSet::on_exit(state).with_system(save_keybindings_system));
+}
+
+fn setup_profiles(app: &mut App) {
+    let state = GameState::Profiles;
+    app.add_system_set(SystemSet::on_update(state).with_system(display_profiles_menu_system));
+}
+
+fn setup_statistics(app: &mut App) {
+    let state = GameState::Statistics;
+    app.add_system_set(SystemSet::on_update(state).with_system(display_statistics_menu_system));
+}
+
+fn setup_achievements(app: &mut App) {
+    let state = GameState::Achievements;
+    app.add_system_set(SystemSet::on_update(state).with_system(display_achievements_menu_system));
+}
+
 fn esc_to_pause_unpause_system(
     mut kb: ResMut<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
     mut state: ResMut<State<GameState>>,
 ) {
-    if kb.just_pressed(KeyCode::Escape) {
+    let start_pressed = gamepad_buttons
+        .get_just_pressed()
+        .any(|button| button.1 == GamepadButtonType::Start);
+    if kb.just_pressed(KeyCode::Escape) || start_pressed {
         match state.current() {
             GameState::Playing => {
                 state.push(GameState::Paused).unwrap();
                 kb.reset(KeyCode::Escape);
             }
-            GameState::Paused => {
+            GameState::Paused
+            | GameState::Options
+            | GameState::Profiles
+            | GameState::Statistics
+            | GameState::Achievements => {
                 state.pop().unwrap();
                 kb.reset(KeyCode::Escape);
             }
@@ -190,10 +387,119 @@ fn esc_to_pause_unpause_system(
 fn run_criteria_if_not_paused(state: Res<State<GameState>>) -> ShouldRun {
     match state.current() {
         GameState::Paused => ShouldRun::No,
+        _ if state.inactives().contains(&GameState::Paused) => ShouldRun::No,
         _ => ShouldRun::Yes,
     }
 }
 
+fn apply_difficulty_system(base: Res<BaseSettings>, mut settings: ResMut<Settings>) {
+    if base.is_changed() {
+        match base.effective() {
+            Ok(effective) => *settings = effective,
+            Err(err) => log::error!("unable to apply difficulty: {}", err),
+        }
+    }
+}
+
+fn apply_volume_settings_system(
+    settings: Res<Settings>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+) {
+    if settings.is_changed() {
+        let volume = &settings.volume;
+        for (sound, volume) in [
+            (SoundAsset::Laser, volume.laser),
+            (SoundAsset::Thruster, volume.thruster),
+            (SoundAsset::ShipExplode, volume.ship_explosion),
+            (SoundAsset::AsteroidExplode, volume.asteroid_explosion),
+            (SoundAsset::Shield, volume.shield),
+        ] {
+            sfx_event.send(SetVolSfx::new(sound, volume).into());
+        }
+    }
+}
+
+fn apply_window_settings_system(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    if settings.is_changed() {
+        let window = windows.get_primary_mut().unwrap();
+        let mode = settings.window.mode.into();
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+        let (width, height) = (settings.window.width as f32, settings.window.height as f32);
+        if settings.window.mode == settings::WindowMode::Windowed
+            && (window.requested_width() != width || window.requested_height() != height)
+        {
+            window.set_resolution(width, height);
+        }
+    }
+}
+
+fn toggle_fullscreen_system(kb: Res<Input<KeyCode>>, mut settings: ResMut<BaseSettings>) {
+    if kb.just_pressed(KeyCode::F11) {
+        settings.window.mode = settings.window.mode.toggled();
+        if let Err(err) = update_user_settings(json!({
+            "window": { "mode": settings.window.mode }
+        })) {
+            log::error!("unable to save window mode: {}", err);
+        }
+    }
+}
+
+/// Adapts the layout to a new window size, and keeps the settings in line with a window
+/// resized by the user.
+fn window_resized_system(
+    mut resized_events: EventReader<WindowResized>,
+    mut background_query: Query<&mut Sprite, With<Background>>,
+    mut settings: ResMut<BaseSettings>,
+) {
+    for event in resized_events
+        .iter()
+        .filter(|event| event.id == WindowId::primary())
+    {
+        for mut sprite in background_query.iter_mut() {
+            sprite.custom_size = Some(Vec2::new(event.width, event.height));
+        }
+
+        let (width, height) = (event.width as u32, event.height as u32);
+        if settings.window.mode == settings::WindowMode::Windowed
+            && (settings.window.width != width || settings.window.height != height)
+        {
+            settings.window.width = width;
+            settings.window.height = height;
+        }
+    }
+}
+
+/// Saves the window size once the user has stopped resizing the window for a while.
+fn remember_window_size_system(
+    settings: Res<BaseSettings>,
+    time: Res<Time>,
+    mut saved_size: Local<Option<(u32, u32)>>,
+    mut pending: Local<Option<Timer>>,
+) {
+    let size = (settings.window.width, settings.window.height);
+    match *saved_size {
+        None => *saved_size = Some(size),
+        Some(saved) if saved != size => {
+            *saved_size = Some(size);
+            *pending = Some(Timer::from_seconds(1., false));
+        }
+        _ => {}
+    }
+
+    if let Some(timer) = pending.as_mut() {
+        if timer.tick(time.delta()).just_finished() {
+            *pending = None;
+            if let Err(err) = update_user_settings(json!({
+                "window": { "width": size.0, "height": size.1 }
+            })) {
+                log::error!("unable to save window size: {}", err);
+            }
+        }
+    }
+}
+
 fn capture_cursor_when_playing_system(state: Res<State<GameState>>, mut windows: ResMut<Windows>) {
     let window = windows.get_primary_mut().unwrap();
     let capture = match state.current() {
@@ -243,11 +549,11 @@ fn egui_defaults_system(mut egui_context: ResMut<EguiContext>) {
 
 fn spawn_menu_asteroids_system(
     settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
     mut spawn_event: EventWriter<SpawnAsteroidEvent>,
     mut background_asteroids_query: Query<Entity, With<BackgroundAsteroid>>,
 ) {
     if background_asteroids_query.iter_mut().next().is_none() {
-        let mut rng = rand::thread_rng();
         for _ in 0..settings.general.asteroids_in_start_menu {
             spawn_event.send(SpawnAsteroidEvent::new(
                 rng.gen_range(settings.asteroid.size_min..settings.asteroid.size_max),
@@ -277,8 +583,8 @@ fn change_background_system(
     background_query: Query<Entity, With<Background>>,
     win_bounds: Res<GfxBounds>,
     settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::thread_rng();
     let bg = BackgroundAsset::iter()
         .nth(rng.gen_range(0..BackgroundAsset::COUNT - 1))
         .unwrap();
@@ -302,18 +608,24 @@ fn change_background_system(
         .insert(FadeIn::from(settings.general.background_fade));
 }
 
-fn prep_playingfield_system(mut commands: Commands) {
+fn prep_playingfield_system(mut commands: Commands, settings: Res<Settings>) {
     // clear asteroid counter
     commands.insert_resource(AsteroidCounter::default());
 
     // reset score
     commands.insert_resource(Score::default());
+
+    // reset lives
+    commands.insert_resource(Lives(settings.player.lives));
+    commands.remove_resource::<Respawn>();
 }
 
 fn clear_playingfield_system(
     mut commands: Commands,
     player_query: Query<Entity, With<Player>>,
     asteroids_query: Query<Entity, With<Asteroid>>,
+    saucers_query: Query<Entity, Or<(With<Saucer>, With<EnemyLaser>)>>,
+    pickups_query: Query<Entity, With<Pickup>>,
 ) {
     player_query
         .iter()
@@ -321,6 +633,12 @@ fn clear_playingfield_system(
     asteroids_query
         .iter()
         .for_each(|e| commands.entity(e).despawn_recursive());
+    saucers_query
+        .iter()
+        .for_each(|e| commands.entity(e).despawn_recursive());
+    pickups_query
+        .iter()
+        .for_each(|e| commands.entity(e).despawn_recursive());
 }
 
 #[derive(Debug,Default,Clone,Component)]
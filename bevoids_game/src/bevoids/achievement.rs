@@ -0,0 +1,164 @@
+use bevoids_assets::SoundAsset;
+use bevy::{log, prelude::*};
+use bevy_effects::sound::{PlaySfx, SfxCmdEvent};
+use std::time::Duration;
+
+use super::{
+    asteroids::{Asteroid, SpawnAsteroidEvent},
+    highscore::Score,
+    input::{ActionState, InputAction},
+    profile::Profiles,
+    settings::{Achievement, Condition, Settings},
+    stats::RunStats,
+};
+
+/// Seconds an unlocked achievement stays on screen.
+const TOAST_SECONDS: f32 = 4.;
+
+/// What the current run has accomplished, as far as the achievement conditions go.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct RunProgress {
+    pub score: u32,
+    pub asteroids_shot: usize,
+    pub duration: Duration,
+    /// Whether the player has thrusted at any point.
+    pub thrusted: bool,
+    /// Whether the field has been cleared of asteroids before thrusting.
+    pub cleared_without_thrust: bool,
+}
+
+impl RunProgress {
+    pub fn meets(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Score(score) => self.score >= *score,
+            Condition::AsteroidsShot(count) => self.asteroids_shot >= *count,
+            Condition::Survive(duration) => self.duration >= *duration,
+            Condition::ClearFieldWithoutThrust => self.cleared_without_thrust,
+        }
+    }
+}
+
+/// An achievement just unlocked, shown until its timer runs out.
+pub(crate) struct Toast {
+    pub name: String,
+    pub description: String,
+    timer: Timer,
+}
+
+/// The achievements just unlocked.
+#[derive(Default)]
+pub(crate) struct AchievementToasts(Vec<Toast>);
+
+impl AchievementToasts {
+    pub fn push(&mut self, achievement: &Achievement) {
+        self.0.push(Toast {
+            name: achievement.name.clone(),
+            description: achievement.description.clone(),
+            timer: Timer::from_seconds(TOAST_SECONDS, false),
+        });
+    }
+
+    /// Advances the timers by `delta`, dropping the toasts that have been shown long enough.
+    pub fn tick(&mut self, delta: Duration) {
+        for toast in &mut self.0 {
+            toast.timer.tick(delta);
+        }
+        self.0.retain(|toast| !toast.timer.finished());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.0.iter()
+    }
+}
+
+pub(crate) fn reset_run_progress_system(mut commands: Commands) {
+    commands.insert_resource(RunProgress::default());
+}
+
+pub(crate) fn track_run_progress_system(
+    mut progress: ResMut<RunProgress>,
+    mut spawn_events: EventReader<SpawnAsteroidEvent>,
+    asteroids_query: Query<Entity, With<Asteroid>>,
+    actions: Res<ActionState>,
+    run_stats: Res<RunStats>,
+    score: Res<Score>,
+) {
+    progress.score = u32::from(*score);
+    progress.asteroids_shot = run_stats.asteroids_shot;
+    progress.duration = run_stats.duration;
+    progress.thrusted |= actions.pressed(InputAction::Accelerate);
+
+    // asteroids about to be spawned, e.g. when splitting, keep the field from being clear
+    let spawning = spawn_events.iter().count() > 0;
+    if !progress.thrusted
+        && !spawning
+        && progress.asteroids_shot > 0
+        && asteroids_query.iter().next().is_none()
+    {
+        progress.cleared_without_thrust = true;
+    }
+}
+
+/// Unlocks the achievements met by the run for the active profile - if any.
+pub(crate) fn unlock_achievements_system(
+    progress: Res<RunProgress>,
+    settings: Res<Settings>,
+    mut profiles: ResMut<Profiles>,
+    mut toasts: ResMut<AchievementToasts>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+) {
+    let profile = match profiles.active_mut() {
+        Some(profile) => profile,
+        None => return,
+    };
+
+    let mut unlocked = false;
+    for achievement in settings
+        .achievements
+        .iter()
+        .filter(|achievement| progress.meets(&achievement.condition))
+    {
+        if profile.unlock(&achievement.name) {
+            log::info!(achievement = ?achievement.name, "achievement unlocked");
+            toasts.push(achievement);
+            sfx_event.send(PlaySfx::new(SoundAsset::Notification).into());
+            unlocked = true;
+        }
+    }
+    if unlocked {
+        profiles.save();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn conditions_are_met_by_the_run() {
+        let progress = RunProgress {
+            score: 500,
+            asteroids_shot: 10,
+            duration: Duration::from_secs(60),
+            ..RunProgress::default()
+        };
+        assert!(progress.meets(&Condition::Score(500)));
+        assert!(!progress.meets(&Condition::Score(501)));
+        assert!(progress.meets(&Condition::AsteroidsShot(10)));
+        assert!(!progress.meets(&Condition::AsteroidsShot(11)));
+        assert!(progress.meets(&Condition::Survive(Duration::from_secs(60))));
+        assert!(!progress.meets(&Condition::Survive(Duration::from_secs(61))));
+        assert!(!progress.meets(&Condition::ClearFieldWithoutThrust));
+    }
+
+    #[test]
+    fn toasts_are_dropped_once_shown() {
+        let achievement = Settings::default().achievements[0].clone();
+        let mut toasts = AchievementToasts::default();
+        toasts.push(&achievement);
+        toasts.tick(Duration::from_secs_f32(TOAST_SECONDS / 2.));
+        assert_eq!(toasts.iter().count(), 1);
+        toasts.tick(Duration::from_secs_f32(TOAST_SECONDS));
+        assert_eq!(toasts.iter().count(), 0);
+    }
+}
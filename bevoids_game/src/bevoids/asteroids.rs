@@ -18,7 +18,9 @@ use crate::{
 
 use super::{
     movement::{spawn_display_shadows, InsideWindow, ShadowController, ShadowOf, Velocity},
+    pickup::SpawnPickupEvent,
     player::Player,
+    rng::GameRng,
     settings::Settings,
 };
 
@@ -51,6 +53,16 @@ pub(crate) struct AsteroidCounter {
     shot: usize,
 }
 
+impl AsteroidCounter {
+    pub fn spawned(&self) -> usize {
+        self.spawned
+    }
+
+    pub fn shot(&self) -> usize {
+        self.shot
+    }
+}
+
 #[derive(Debug, Component)]
 pub(crate) struct AsteroidsSpawner {
     delay: Duration,
@@ -74,6 +86,7 @@ pub(crate) fn asteroid_spawner_system(
     asteroids_query: Query<&Asteroid>,
     settings: Res<Settings>,
     time: Res<Time>,
+    mut rng: ResMut<GameRng>,
 ) {
     let mut spawner_data = spawner_query.iter_mut().next().unwrap();
 
@@ -100,8 +113,7 @@ pub(crate) fn asteroid_spawner_system(
             spawner_data.paused = false;
             log::warn!("field empty - spawning asteroid");
             spawn_event.send(SpawnAsteroidEvent::new(
-                rand::thread_rng()
-                    .gen_range(settings.asteroid.size_min..settings.asteroid.size_max),
+                rng.gen_range(settings.asteroid.size_min..settings.asteroid.size_max),
                 None,
                 false,
             ));
@@ -119,8 +131,7 @@ pub(crate) fn asteroid_spawner_system(
             spawner_data.delay = delay;
             log::warn!(duration=?delay, "spawning planned asteroid");
             spawn_event.send(SpawnAsteroidEvent::new(
-                rand::thread_rng()
-                    .gen_range(settings.asteroid.size_min..settings.asteroid.size_max),
+                rng.gen_range(settings.asteroid.size_min..settings.asteroid.size_max),
                 None,
                 false,
             ));
@@ -134,10 +145,12 @@ pub(crate) fn shot_asteroid_system(
     mut spawn_event: EventWriter<SpawnAsteroidEvent>,
     mut remove_event: EventWriter<AsteroidExplosionEvent>,
     mut score_event: EventWriter<AddScoreEvent>,
+    mut pickup_event: EventWriter<SpawnPickupEvent>,
     mut counter: ResMut<AsteroidCounter>,
     transform_and_bounds_query: Query<(&Transform, &GfxBounds), With<Asteroid>>,
     shadowof_query: Query<&ShadowOf, With<Asteroid>>,
     settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
 ) {
     let shot_asteroids = shot_events
         .iter()
@@ -166,6 +179,11 @@ pub(crate) fn shot_asteroid_system(
                 * settings.asteroid.max_score) as u32,
         )));
 
+        // drop pickup
+        if rng.gen_bool(settings.pickup.drop_chance.into()) {
+            pickup_event.send(SpawnPickupEvent::new(asteroid_tf.translation));
+        }
+
         // spawn split asteroids
         let split_size = asteroid_bounds.size().max_element() * settings.asteroid.split_size_factor;
         if split_size >= settings.asteroid.size_min {
@@ -203,6 +221,7 @@ pub(crate) fn spawn_asteroid_event_system(
     player_tf_query: Query<&Transform, (With<Player>, With<ShadowController>)>,
     window_bounds: Res<GfxBounds>,
     settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
 ) {
     let player_tf = player_tf_query.iter().next();
 
@@ -214,10 +233,9 @@ pub(crate) fn spawn_asteroid_event_system(
         .iter()
         .filter(|&e| e.size >= settings.asteroid.size_min)
     {
-        let mut rng = rand::thread_rng();
-
         let position = position.unwrap_or_else(|| {
             random_2d_position_no_closer_than(
+                &mut *rng,
                 player_tf,
                 settings.asteroid.spawn_player_distance,
                 &window_bounds,
@@ -290,11 +308,11 @@ pub(crate) fn spawn_asteroid_event_system(
 }
 
 fn random_2d_position_no_closer_than(
+    rng: &mut impl Rng,
     position: Option<&Transform>,
     distance: f32,
     window_bounds: &Res<GfxBounds>,
 ) -> Vec2 {
-    let mut rng = rand::thread_rng();
     loop {
         let rnd_position = {
             let (w, h) = (window_bounds.width() / 2.0, window_bounds.height() / 2.0);
@@ -0,0 +1,9 @@
+use directories::ProjectDirs;
+use std::env;
+
+/// Platform specific directories, named after the running executable.
+pub(crate) fn project_dirs() -> Option<ProjectDirs> {
+    let current_exe = env::current_exe().ok()?;
+    let application = current_exe.file_stem()?.to_str()?;
+    ProjectDirs::from("", "", application)
+}
@@ -0,0 +1,88 @@
+use chrono::Utc;
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// `path` with `suffix` appended to its file name, e.g. `settings.json.tmp`.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map_or_else(OsString::new, OsString::from);
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Moves the corrupt file at `path` aside to a timestamped backup, rather than it being
+/// overwritten by the next save, returning where it went.
+pub(crate) fn move_to_backup(path: &Path) -> io::Result<PathBuf> {
+    let backup = backup_path(path);
+    fs::rename(path, &backup)?;
+    Ok(backup)
+}
+
+/// A timestamped backup of `path`, counting up should a backup from the same second exist.
+fn backup_path(path: &Path) -> PathBuf {
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%S");
+    let mut backup = with_suffix(path, &format!(".corrupt-{}.bak", timestamp));
+    let mut count = 0;
+    while backup.exists() {
+        count += 1;
+        backup = with_suffix(path, &format!(".corrupt-{}-{}.bak", timestamp, count));
+    }
+    backup
+}
+
+/// Writes `content` to a temporary file, then moves it in place of `path` - a failed write
+/// never leaves a half written file behind.
+pub(crate) fn write_atomically(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let temp_path = with_suffix(path, ".tmp");
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path).map_err(|err| {
+        fs::remove_file(&temp_path).ok();
+        err
+    })
+}
+
+/// A directory of its own for a test, removed along with its content once dropped.
+#[cfg(test)]
+pub(crate) struct TestDir(PathBuf);
+
+#[cfg(test)]
+impl TestDir {
+    #[must_use]
+    pub fn new() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "bevoids-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        // left over by an earlier run that crashed
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).expect("unable to create test directory");
+        Self(dir)
+    }
+
+    /// Path of the file `name` in the directory.
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+
+    /// Backups moved aside in the directory, see [move_to_backup].
+    pub fn backups(&self) -> Vec<PathBuf> {
+        fs::read_dir(&self.0)
+            .expect("unable to list test directory")
+            .map(|entry| entry.expect("unable to list test directory").path())
+            .filter(|path| path.to_string_lossy().ends_with(".bak"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
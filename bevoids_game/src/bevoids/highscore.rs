@@ -1,14 +1,48 @@
-use bevy::{log, prelude::*};
+use bevy::{log, prelude::*, tasks::IoTaskPool};
 use chrono::{DateTime, Utc};
 use derive_more::{Add, AddAssign, Constructor, Display, From, Into};
 use serde::{Deserialize, Serialize};
-use std::{env, path::PathBuf};
 
-use crate::bevoids::settings::Settings;
+use crate::bevoids::{pickup::PowerUps, settings::Settings, stats::RunStats};
+
+mod backend;
+mod boards;
+mod http;
+mod service;
+mod signing;
+mod storage;
+mod transfer;
+
+use backend::{FallbackBackend, FileBackend, UploadQueue};
+use http::HttpBackend;
+use service::Outcome;
+use storage::StorageError;
+
+pub(crate) use boards::{BoardCapacities, BoardKey, GameMode, HighScoreBoards};
+pub(crate) use service::HighScoreService;
+pub(crate) use storage::HighScoresFile;
+pub(crate) use transfer::{default_path, export, export_file, import, import_file};
 
 #[derive(Debug)]
 pub(crate) struct AddScoreEvent(pub Score);
 
+/// Problem with storing the highscores, shown to the user while in the menus - until the
+/// highscores are loaded or saved successfully again.
+#[derive(Debug)]
+pub(crate) struct HighScoreWarning(pub String);
+
+/// Outcome of the last export or import of highscores, shown in the highscore menu.
+#[derive(Debug)]
+pub(crate) struct HighScoreNotice(pub String);
+
+/// Rank of the player who last submitted a highscore, on the board it was submitted to.
+#[derive(Debug)]
+pub(crate) struct PlayerRank {
+    pub board: BoardKey,
+    pub name: String,
+    pub rank: Option<usize>,
+}
+
 #[derive(
     Debug,
     Display,
@@ -29,14 +63,33 @@ pub(crate) struct AddScoreEvent(pub Score);
 )]
 pub(crate) struct Score(u32);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct HighScore {
     score: Score,
     name: String,
     time: DateTime<Utc>,
+    #[serde(default)]
+    mode: GameMode,
+    #[serde(default = "default_difficulty")]
+    difficulty: String,
+    /// Statistics of the run - missing from scores made before these were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<RunStats>,
+    /// Set once the highscore has failed to verify against the signature of its board.
+    #[serde(default, skip_serializing_if = "is_false")]
+    unverified: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Scores from before difficulties were introduced were all made on normal difficulty.
+fn default_difficulty() -> String {
+    "Normal".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct HighScoreRepository {
     scores: Vec<HighScore>,
     max_records: u8,
@@ -52,6 +105,19 @@ impl HighScoreRepository {
         }
     }
 
+    /// Sorts the highscores, keeping at most `max_records` of them.
+    pub fn set_capacity(&mut self, max_records: u8) {
+        self.scores.sort_by(|h1, h2| h2.score.cmp(&h1.score));
+        self.scores.truncate(max_records as usize);
+        self.max_records = max_records;
+    }
+
+    /// Replaces the highscores with `scores`, best first.
+    pub fn set_scores(&mut self, mut scores: Vec<HighScore>) {
+        scores.truncate(self.max_records as usize);
+        self.scores = scores;
+    }
+
     #[allow(dead_code)]
     pub fn iter(&self) -> impl Iterator<Item = &HighScore> {
         self.scores.iter()
@@ -124,14 +190,24 @@ impl HighScoreRepository {
 impl HighScore {
     #[allow(dead_code)]
     #[must_use]
-    pub fn new<T: Into<String>>(score: Score, name: T) -> Self {
+    pub fn new<T: Into<String>, D: Into<String>>(score: Score, name: T, difficulty: D) -> Self {
         Self {
             score,
             name: name.into(),
             time: Utc::now(),
+            mode: GameMode::Classic,
+            difficulty: difficulty.into(),
+            stats: None,
+            unverified: false,
         }
     }
 
+    #[must_use]
+    pub fn with_stats(mut self, stats: RunStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn score(&self) -> Score {
         self.score
@@ -146,6 +222,20 @@ impl HighScore {
     pub fn time(&self) -> &DateTime<Utc> {
         &self.time
     }
+
+    /// The board this highscore belongs on.
+    pub fn key(&self) -> BoardKey {
+        BoardKey::new(self.mode, &self.difficulty)
+    }
+
+    pub fn stats(&self) -> Option<&RunStats> {
+        self.stats.as_ref()
+    }
+
+    /// Whether the highscore failed to verify, e.g. after editing the highscores file by hand.
+    pub fn unverified(&self) -> bool {
+        self.unverified
+    }
 }
 
 #[cfg(test)]
@@ -162,6 +252,30 @@ mod test {
         assert_send::<HighScoreRepository>();
     }
 
+    #[test]
+    fn highscore_without_difficulty_is_normal() {
+        let highscore: HighScore = serde_json::from_str(
+            r#"{ "score": 10, "name": "abc", "time": "2022-01-01T00:00:00Z" }"#,
+        )
+        .unwrap();
+        assert_eq!(highscore.key(), BoardKey::new(GameMode::Classic, "Normal"));
+        assert_eq!(highscore.stats(), None);
+    }
+
+    #[test]
+    fn highscore_stats_are_kept() {
+        let stats = RunStats {
+            asteroids_shot: 3,
+            lasers_fired: 4,
+            ..RunStats::default()
+        };
+        let highscore = HighScore::new(Score::new(10), "abc", "Normal").with_stats(stats);
+        let json = serde_json::to_string(&highscore).unwrap();
+
+        let highscore: HighScore = serde_json::from_str(&json).unwrap();
+        assert_eq!(highscore.stats(), Some(&stats));
+    }
+
     #[test]
     fn default_creates_an_empty_board() {
         let highscores = HighScoreRepository::with_capacity(5);
@@ -176,7 +290,7 @@ mod test {
 
         let mut highscores = HighScoreRepository::with_capacity(count);
         for _ in 0..count {
-            let _ = highscores.push(HighScore::new(rand::random::<u32>().into(), ""));
+            let _ = highscores.push(HighScore::new(rand::random::<u32>().into(), "", ""));
         }
         assert!(highscores
             .iter()
@@ -185,11 +299,17 @@ mod test {
             .all(|(a, b)| a >= b));
 
         assert!(
-            matches!(highscores.push(HighScore::new(u32::MAX.into(), "")), Ok(_)),
+            matches!(
+                highscores.push(HighScore::new(u32::MAX.into(), "", "")),
+                Ok(_)
+            ),
             "Highest highscore should always succeeed"
         );
         assert!(
-            matches!(highscores.push(HighScore::new(u32::MIN.into(), "")), Err(_)),
+            matches!(
+                highscores.push(HighScore::new(u32::MIN.into(), "", "")),
+                Err(_)
+            ),
             "Low scores are not making it to the highscore list"
         );
         assert_eq!(
@@ -204,12 +324,15 @@ mod test {
 pub(crate) fn update_score_system(
     mut addscore_events: EventReader<AddScoreEvent>,
     mut score: ResMut<Score>,
+    power_ups: Res<PowerUps>,
+    settings: Res<Settings>,
 ) {
     let score_sum = Score::from(
         addscore_events
             .iter()
             .map(|e| -> u32 { u32::from(e.0) })
-            .sum::<u32>(),
+            .sum::<u32>()
+            * power_ups.score_multiplier(&settings),
     );
     if u32::from(score_sum) > 0 {
         *score += score_sum;
@@ -221,37 +344,95 @@ pub(crate) fn update_score_system(
     }
 }
 
-pub(crate) fn load_highscores(mut commands: Commands, settings: Res<Settings>) {
-    let pb = highscores_path();
-    if let Ok(content) = std::fs::read_to_string(pb.as_path()) {
-        let highscores: Result<HighScoreRepository, _> = serde_json::from_str(&content);
-        if let Ok(mut highscores) = highscores {
-            highscores.scores.sort_by(|h1, h2| h2.score.cmp(&h1.score));
-            highscores
-                .scores
-                .truncate(settings.general.highscores_capacity as usize);
-            highscores.max_records = settings.general.highscores_capacity;
-            commands.insert_resource(highscores);
-            return;
+pub(crate) fn load_highscores(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    file: Option<Res<HighScoresFile>>,
+    pool: Res<IoTaskPool>,
+) {
+    let capacities = BoardCapacities::from(&*settings);
+    let file = file.map(|file| file.clone());
+    let (local, error) = FileBackend::open(
+        file.clone(),
+        capacities.clone(),
+        settings.general.unverified_highscores,
+    );
+    match error {
+        Some(err) => {
+            log::warn!("unable to load highscores: {}", err);
+            commands.insert_resource(HighScoreWarning(err.to_string()));
         }
+        None => commands.remove_resource::<HighScoreWarning>(),
     }
-    let highscores = HighScoreRepository::with_capacity(settings.general.highscores_capacity);
-    save_highscores(&highscores);
-    commands.insert_resource(highscores);
+    commands.insert_resource(local.boards().clone());
+
+    let service = match &settings.general.leaderboard_url {
+        Some(url) => {
+            let queue = UploadQueue::open(file.map(|file| file.upload_queue()));
+            let service =
+                HighScoreService::new(FallbackBackend::new(HttpBackend::new(url), local, queue));
+            service.refresh(&pool, all_boards(&settings, &capacities));
+            service
+        }
+        None => HighScoreService::new(local),
+    };
+    commands.insert_resource(service);
+}
+
+pub(crate) fn refresh_highscores_system(
+    service: Res<HighScoreService>,
+    settings: Res<Settings>,
+    pool: Res<IoTaskPool>,
+) {
+    let capacities = BoardCapacities::from(&*settings);
+    service.refresh(&pool, all_boards(&settings, &capacities));
 }
 
-fn highscores_path() -> PathBuf {
-    let current_exe = env::current_exe().unwrap();
-    let application = current_exe.file_stem().unwrap().to_str().unwrap();
-    let project_dirs = directories::ProjectDirs::from("", "", application).unwrap();
-    let mut pb = PathBuf::from(project_dirs.data_dir());
-    std::fs::create_dir_all(pb.as_path()).ok();
-    pb.push("highscores.json");
-    pb
+/// Every board known to the `settings`, along with the number of highscores kept on each.
+pub(crate) fn all_boards(
+    settings: &Settings,
+    capacities: &BoardCapacities,
+) -> Vec<(BoardKey, usize)> {
+    BoardKey::all(settings)
+        .map(|key| {
+            let capacity = capacities.of(&key) as usize;
+            (key, capacity)
+        })
+        .collect()
 }
 
-pub(crate) fn save_highscores(highscores: &HighScoreRepository) {
-    let pb = highscores_path();
-    let highscores = serde_json::to_string_pretty(&highscores).unwrap();
-    std::fs::write(pb, highscores).expect("unable to write highscores");
+pub(crate) fn apply_highscore_outcomes_system(
+    mut commands: Commands,
+    service: Res<HighScoreService>,
+    settings: Res<Settings>,
+    mut boards: ResMut<HighScoreBoards>,
+) {
+    let capacities = BoardCapacities::from(&*settings);
+    for outcome in service.take_outcomes() {
+        match outcome {
+            Outcome::Board { key, scores } => {
+                let capacity = capacities.of(&key);
+                boards.set_board(key, scores, capacity);
+            }
+            Outcome::Ranked { key, name, rank } => {
+                commands.remove_resource::<HighScoreWarning>();
+                commands.insert_resource(PlayerRank {
+                    board: key,
+                    name,
+                    rank,
+                });
+            }
+            Outcome::Imported(imported) => {
+                commands.remove_resource::<HighScoreWarning>();
+                commands.insert_resource(HighScoreNotice(format!(
+                    "Imported {} new highscores",
+                    imported
+                )));
+            }
+            Outcome::Failed(err) => {
+                log::warn!("highscores failed: {}", err);
+                commands.insert_resource(HighScoreWarning(format!("Highscores: {}", err)));
+            }
+        }
+    }
 }
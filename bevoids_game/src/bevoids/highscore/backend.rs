@@ -0,0 +1,418 @@
+use bevy::log;
+use derive_more::{Display, From};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::bevoids::{
+    files::{move_to_backup, write_atomically},
+    settings::UnverifiedHighScores,
+};
+
+use super::{BoardCapacities, BoardKey, HighScore, HighScoreBoards, HighScoresFile, StorageError};
+
+/// Errors encountered by a [HighScoreBackend].
+#[derive(Debug, Display, From)]
+pub(crate) enum BackendError {
+    #[display(fmt = "{}", _0)]
+    Storage(StorageError),
+    #[display(fmt = "leaderboard could not be reached: {}", _0)]
+    #[from(ignore)]
+    Unreachable(String),
+    #[display(fmt = "leaderboard failed: {}", _0)]
+    #[from(ignore)]
+    Remote(String),
+}
+
+impl std::error::Error for BackendError {}
+
+/// Somewhere highscores are kept, on separate boards for each game mode and difficulty.
+///
+/// Ranks start from 0, being the best.
+pub(crate) trait HighScoreBackend: Send + Sync {
+    /// Adds `highscore` to its board, returning its rank if it made it onto the board.
+    fn submit(&mut self, highscore: &HighScore) -> Result<Option<usize>, BackendError>;
+
+    /// The `count` best highscores on the board `key`, best first.
+    fn top(&mut self, key: &BoardKey, count: usize) -> Result<Vec<HighScore>, BackendError>;
+
+    /// Rank of the best highscore by `name`, if it is on the board `key`.
+    fn rank(&mut self, key: &BoardKey, name: &str) -> Result<Option<usize>, BackendError>;
+
+    /// Adds the `highscores` made elsewhere, returning how many made it onto the boards.
+    ///
+    /// By default each is submitted in turn.
+    fn import(&mut self, highscores: &[HighScore]) -> Result<usize, BackendError> {
+        let mut imported = 0;
+        for highscore in highscores {
+            if self.submit(highscore)?.is_some() {
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+}
+
+/// Highscores kept in the local [HighScoresFile] - or only in memory, without one.
+pub(crate) struct FileBackend {
+    file: Option<HighScoresFile>,
+    boards: HighScoreBoards,
+    capacities: BoardCapacities,
+}
+
+impl FileBackend {
+    /// Opens the highscores in `file`, dropping those failing to verify if told to.
+    ///
+    /// The boards start out empty if the file could not be loaded, returning the reason along.
+    pub fn open(
+        file: Option<HighScoresFile>,
+        capacities: BoardCapacities,
+        unverified: UnverifiedHighScores,
+    ) -> (Self, Option<StorageError>) {
+        let (mut boards, error) = match file.as_ref().map(HighScoresFile::load) {
+            Some(Ok(Some(boards))) => (boards, None),
+            Some(Ok(None)) | None => (HighScoreBoards::default(), None),
+            Some(Err(err)) => (HighScoreBoards::default(), Some(err)),
+        };
+        if unverified == UnverifiedHighScores::Drop {
+            let dropped = boards.drop_unverified();
+            if dropped > 0 {
+                log::warn!("dropped {} highscores failing to verify", dropped);
+            }
+        }
+        boards.set_capacities(&capacities);
+        (
+            Self {
+                file,
+                boards,
+                capacities,
+            },
+            error,
+        )
+    }
+
+    pub fn boards(&self) -> &HighScoreBoards {
+        &self.boards
+    }
+
+    /// Adds the `highscores` not already on the boards, returning those that made it.
+    fn merge(&mut self, highscores: &[HighScore]) -> Result<Vec<HighScore>, BackendError> {
+        let merged = self
+            .boards
+            .merge(highscores.iter().cloned(), &self.capacities);
+        if let (false, Some(file)) = (merged.is_empty(), &self.file) {
+            file.save(&self.boards)?;
+        }
+        Ok(merged)
+    }
+}
+
+impl HighScoreBackend for FileBackend {
+    fn submit(&mut self, highscore: &HighScore) -> Result<Option<usize>, BackendError> {
+        let rank = self.boards.push(highscore.clone(), &self.capacities).ok();
+        if let (Some(_), Some(file)) = (rank, &self.file) {
+            file.save(&self.boards)?;
+        }
+        Ok(rank)
+    }
+
+    fn top(&mut self, key: &BoardKey, count: usize) -> Result<Vec<HighScore>, BackendError> {
+        Ok(self
+            .boards
+            .board(key)
+            .map(|board| board.iter().take(count).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn rank(&mut self, key: &BoardKey, name: &str) -> Result<Option<usize>, BackendError> {
+        Ok(self
+            .boards
+            .board(key)
+            .and_then(|board| board.iter().position(|h| h.name() == name)))
+    }
+
+    fn import(&mut self, highscores: &[HighScore]) -> Result<usize, BackendError> {
+        Ok(self.merge(highscores)?.len())
+    }
+}
+
+/// Highscores kept by a `remote` leaderboard, falling back to the `local` ones while the remote
+/// cannot be reached.
+///
+/// Highscores are always kept locally as well. Those submitted while the remote is unreachable
+/// are queued, and uploaded as soon as it can be reached again.
+pub(crate) struct FallbackBackend<R> {
+    remote: R,
+    local: FileBackend,
+    queue: UploadQueue,
+}
+
+impl<R: HighScoreBackend> FallbackBackend<R> {
+    #[must_use]
+    pub fn new(remote: R, local: FileBackend, queue: UploadQueue) -> Self {
+        Self {
+            remote,
+            local,
+            queue,
+        }
+    }
+
+    /// Uploads the queued highscores, stopping if the remote cannot be reached.
+    fn flush(&mut self) -> Result<(), BackendError> {
+        while let Some(highscore) = self.queue.pending.first() {
+            match self.remote.submit(highscore) {
+                Ok(_) => {}
+                Err(err @ BackendError::Unreachable(_)) => return Err(err),
+                Err(err) => log::warn!("dropping queued highscore: {}", err),
+            }
+            self.queue.pending.remove(0);
+            self.queue.save();
+        }
+        Ok(())
+    }
+}
+
+impl<R: HighScoreBackend> HighScoreBackend for FallbackBackend<R> {
+    fn submit(&mut self, highscore: &HighScore) -> Result<Option<usize>, BackendError> {
+        let local = self.local.submit(highscore);
+        match self.flush().and_then(|_| self.remote.submit(highscore)) {
+            Err(BackendError::Unreachable(err)) => {
+                log::warn!("leaderboard unreachable, queueing highscore: {}", err);
+                self.queue.pending.push(highscore.clone());
+                self.queue.save();
+                local
+            }
+            remote => {
+                if let Err(err) = local {
+                    log::warn!("unable to keep highscore locally: {}", err);
+                }
+                remote
+            }
+        }
+    }
+
+    fn top(&mut self, key: &BoardKey, count: usize) -> Result<Vec<HighScore>, BackendError> {
+        match self.flush().and_then(|_| self.remote.top(key, count)) {
+            Err(BackendError::Unreachable(_)) => self.local.top(key, count),
+            remote => remote,
+        }
+    }
+
+    fn rank(&mut self, key: &BoardKey, name: &str) -> Result<Option<usize>, BackendError> {
+        match self.flush().and_then(|_| self.remote.rank(key, name)) {
+            Err(BackendError::Unreachable(_)) => self.local.rank(key, name),
+            remote => remote,
+        }
+    }
+
+    /// Imports the `highscores` locally, queueing those new to this machine for upload.
+    fn import(&mut self, highscores: &[HighScore]) -> Result<usize, BackendError> {
+        let merged = self.local.merge(highscores)?;
+        let imported = merged.len();
+        self.queue.pending.extend(merged);
+        self.queue.save();
+        if let Err(err) = self.flush() {
+            log::warn!(
+                "leaderboard unreachable, keeping imported highscores queued: {}",
+                err
+            );
+        }
+        Ok(imported)
+    }
+}
+
+/// Highscores waiting to be uploaded, kept in a file so they survive restarting the game.
+pub(crate) struct UploadQueue {
+    path: Option<PathBuf>,
+    pending: Vec<HighScore>,
+}
+
+impl UploadQueue {
+    /// Opens the queue kept in `path` - or only in memory, without a path.
+    #[must_use]
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let pending = path.as_deref().map(Self::read).unwrap_or_default();
+        Self { path, pending }
+    }
+
+    /// Reads the highscores queued in `path`, moving a corrupt file aside to a backup rather
+    /// than having the next save overwrite it.
+    fn read(path: &Path) -> Vec<HighScore> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                match move_to_backup(path) {
+                    Ok(backup) => {
+                        log::warn!(?path, ?backup, "moved corrupt upload queue aside: {}", err)
+                    }
+                    Err(backup_err) => {
+                        log::warn!(
+                            ?path,
+                            "unable to back up corrupt upload queue: {}",
+                            backup_err
+                        )
+                    }
+                }
+                Vec::new()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                log::warn!(?path, "unable to read upload queue: {}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            let result = if self.pending.is_empty() {
+                fs::remove_file(path).or_else(|err| match err.kind() {
+                    io::ErrorKind::NotFound => Ok(()),
+                    _ => Err(err),
+                })
+            } else {
+                let content = serde_json::to_string_pretty(&self.pending)
+                    .expect("highscores are serializable");
+                write_atomically(path, content)
+            };
+            if let Err(err) = result {
+                log::warn!(?path, "unable to save upload queue: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::{
+        files::TestDir,
+        highscore::{GameMode, Score},
+        settings::Settings,
+    };
+
+    /// A remote that is never reachable.
+    struct Offline;
+
+    impl HighScoreBackend for Offline {
+        fn submit(&mut self, _: &HighScore) -> Result<Option<usize>, BackendError> {
+            Err(BackendError::Unreachable("offline".into()))
+        }
+
+        fn top(&mut self, _: &BoardKey, _: usize) -> Result<Vec<HighScore>, BackendError> {
+            Err(BackendError::Unreachable("offline".into()))
+        }
+
+        fn rank(&mut self, _: &BoardKey, _: &str) -> Result<Option<usize>, BackendError> {
+            Err(BackendError::Unreachable("offline".into()))
+        }
+    }
+
+    /// A remote keeping its highscores in memory.
+    struct Online(FileBackend);
+
+    impl HighScoreBackend for Online {
+        fn submit(&mut self, highscore: &HighScore) -> Result<Option<usize>, BackendError> {
+            self.0.submit(highscore)
+        }
+
+        fn top(&mut self, key: &BoardKey, count: usize) -> Result<Vec<HighScore>, BackendError> {
+            self.0.top(key, count)
+        }
+
+        fn rank(&mut self, key: &BoardKey, name: &str) -> Result<Option<usize>, BackendError> {
+            self.0.rank(key, name)
+        }
+    }
+
+    fn local() -> FileBackend {
+        let settings = Settings::default();
+        FileBackend::open(
+            None,
+            BoardCapacities::from(&settings),
+            settings.general.unverified_highscores,
+        )
+        .0
+    }
+
+    fn normal() -> BoardKey {
+        BoardKey::new(GameMode::Classic, "Normal")
+    }
+
+    #[test]
+    fn file_backend_ranks_highscores() {
+        let mut backend = local();
+        backend
+            .submit(&HighScore::new(Score::new(10), "abc", "Normal"))
+            .unwrap();
+        backend
+            .submit(&HighScore::new(Score::new(20), "def", "Normal"))
+            .unwrap();
+
+        assert_eq!(backend.rank(&normal(), "abc").unwrap(), Some(1));
+        assert_eq!(backend.rank(&normal(), "ghi").unwrap(), None);
+        assert_eq!(backend.top(&normal(), 1).unwrap()[0].name(), "def");
+        assert!(backend
+            .top(&BoardKey::new(GameMode::Classic, "Hard"), 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn unreachable_remote_falls_back_to_local_and_queues() {
+        let mut backend = FallbackBackend::new(Offline, local(), UploadQueue::open(None));
+        let rank = backend
+            .submit(&HighScore::new(Score::new(10), "abc", "Normal"))
+            .unwrap();
+
+        assert_eq!(rank, Some(0));
+        assert_eq!(backend.top(&normal(), 5).unwrap().len(), 1);
+        assert_eq!(backend.queue.pending.len(), 1);
+    }
+
+    #[test]
+    fn queued_highscores_are_uploaded_once_reachable() {
+        let mut offline = FallbackBackend::new(Offline, local(), UploadQueue::open(None));
+        offline
+            .submit(&HighScore::new(Score::new(10), "abc", "Normal"))
+            .unwrap();
+
+        let mut online = FallbackBackend::new(Online(local()), local(), offline.queue);
+        assert_eq!(online.rank(&normal(), "abc").unwrap(), Some(0));
+        assert!(online.queue.pending.is_empty());
+    }
+
+    #[test]
+    fn imported_highscores_are_kept_once_and_uploaded() {
+        let highscores = [
+            HighScore::new(Score::new(10), "abc", "Normal"),
+            HighScore::new(Score::new(20), "def", "Normal"),
+        ];
+        let mut backend = FallbackBackend::new(Online(local()), local(), UploadQueue::open(None));
+        assert_eq!(backend.import(&highscores).unwrap(), 2);
+        assert_eq!(backend.import(&highscores).unwrap(), 0);
+
+        assert_eq!(backend.local.top(&normal(), 5).unwrap().len(), 2);
+        assert_eq!(backend.remote.top(&normal(), 5).unwrap().len(), 2);
+        assert!(backend.queue.pending.is_empty());
+    }
+
+    #[test]
+    fn corrupt_upload_queue_is_moved_aside() {
+        let dir = TestDir::new();
+        let path = dir.join("highscores.json.queue");
+        fs::write(&path, "[ not json").unwrap();
+
+        let mut queue = UploadQueue::open(Some(path.clone()));
+        assert!(queue.pending.is_empty());
+        queue
+            .pending
+            .push(HighScore::new(Score::new(10), "abc", "Normal"));
+        queue.save();
+
+        assert_eq!(UploadQueue::open(Some(path)).pending.len(), 1);
+        let backups = dir.backups();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "[ not json");
+    }
+}
@@ -0,0 +1,309 @@
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::bevoids::settings::Settings;
+
+use super::{HighScore, HighScoreRepository, Score};
+
+/// The kinds of games played, each with its own highscore boards.
+#[derive(
+    Debug, Display, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub(crate) enum GameMode {
+    Classic,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Classic
+    }
+}
+
+/// Identifies a highscore board.
+#[derive(Debug, Display, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[display(fmt = "{} {}", mode, difficulty)]
+pub(crate) struct BoardKey {
+    pub mode: GameMode,
+    pub difficulty: String,
+}
+
+impl BoardKey {
+    #[must_use]
+    pub fn new<D: Into<String>>(mode: GameMode, difficulty: D) -> Self {
+        Self {
+            mode,
+            difficulty: difficulty.into(),
+        }
+    }
+
+    /// Boards of every mode and difficulty known to the `settings`.
+    pub fn all(settings: &Settings) -> impl Iterator<Item = BoardKey> + '_ {
+        settings
+            .difficulties
+            .iter()
+            .map(|difficulty| BoardKey::new(GameMode::Classic, &difficulty.name))
+    }
+}
+
+/// Number of highscores kept on each board.
+#[derive(Debug, Clone)]
+pub(crate) struct BoardCapacities {
+    default: u8,
+    difficulties: HashMap<String, u8>,
+}
+
+impl BoardCapacities {
+    pub fn of(&self, key: &BoardKey) -> u8 {
+        self.difficulties
+            .get(&key.difficulty)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl From<&Settings> for BoardCapacities {
+    fn from(settings: &Settings) -> Self {
+        Self {
+            default: settings.general.highscores_capacity,
+            difficulties: settings
+                .difficulties
+                .iter()
+                .filter_map(|d| Some((d.name.clone(), d.highscores_capacity?)))
+                .collect(),
+        }
+    }
+}
+
+/// Highscores on separate boards for each game mode and difficulty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "StoredBoards", into = "StoredBoards")]
+pub(crate) struct HighScoreBoards {
+    boards: BTreeMap<BoardKey, HighScoreRepository>,
+}
+
+/// All highscores kept in a single list, as they were before there were separate boards.
+#[derive(Serialize, Deserialize)]
+struct StoredBoards {
+    scores: Vec<HighScore>,
+}
+
+impl HighScoreBoards {
+    pub fn board(&self, key: &BoardKey) -> Option<&HighScoreRepository> {
+        self.boards.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &BoardKey> {
+        self.boards.keys()
+    }
+
+    /// The highscores of every board.
+    pub fn scores(&self) -> impl Iterator<Item = &HighScore> {
+        self.boards.values().flat_map(|board| board.iter())
+    }
+
+    /// Where `score` would be placed on the board `key`.
+    pub fn position(&self, key: &BoardKey, score: &Score) -> Option<usize> {
+        self.board(key)
+            .map_or(Some(0), |board| board.position(score))
+    }
+
+    /// Adds `highscore` to its board, returning its rank if it made it onto the board.
+    pub fn push(
+        &mut self,
+        highscore: HighScore,
+        capacities: &BoardCapacities,
+    ) -> Result<usize, ()> {
+        let key = highscore.key();
+        let capacity = capacities.of(&key);
+        self.boards
+            .entry(key)
+            .or_insert_with(|| HighScoreRepository::with_capacity(capacity))
+            .push(highscore)
+    }
+
+    /// Adds the `highscores` not already on their boards - judged by name, score and time -
+    /// returning those that made it onto the boards.
+    pub fn merge(
+        &mut self,
+        highscores: impl IntoIterator<Item = HighScore>,
+        capacities: &BoardCapacities,
+    ) -> Vec<HighScore> {
+        let mut merged = Vec::new();
+        for highscore in highscores {
+            let known = self
+                .board(&highscore.key())
+                .into_iter()
+                .flat_map(|board| board.iter())
+                .any(|h| {
+                    h.score == highscore.score
+                        && h.name == highscore.name
+                        && h.time == highscore.time
+                });
+            if !known && self.push(highscore.clone(), capacities).is_ok() {
+                merged.push(highscore);
+            }
+        }
+        merged
+    }
+
+    /// Replaces the highscores of the board `key` with `scores`, best first.
+    pub fn set_board(&mut self, key: BoardKey, scores: Vec<HighScore>, capacity: u8) {
+        let board = self
+            .boards
+            .entry(key)
+            .or_insert_with(|| HighScoreRepository::with_capacity(capacity));
+        board.set_capacity(capacity);
+        board.set_scores(scores);
+    }
+
+    /// Flags the highscores of every board failing to `verify`, returning the number newly flagged.
+    pub fn flag_unverified(
+        &mut self,
+        mut verify: impl FnMut(&BoardKey, &[HighScore]) -> bool,
+    ) -> usize {
+        let mut flagged = 0;
+        for (key, board) in self.boards.iter_mut() {
+            if !verify(key, &board.scores) {
+                for highscore in board.scores.iter_mut().filter(|h| !h.unverified) {
+                    highscore.unverified = true;
+                    flagged += 1;
+                }
+            }
+        }
+        flagged
+    }
+
+    /// Removes the flagged highscores from every board, returning the number removed.
+    pub fn drop_unverified(&mut self) -> usize {
+        let mut dropped = 0;
+        for board in self.boards.values_mut() {
+            let count = board.scores.len();
+            board.scores.retain(|h| !h.unverified);
+            dropped += count - board.scores.len();
+        }
+        dropped
+    }
+
+    /// Sorts the highscores of every board, keeping at most the capacity of each.
+    pub fn set_capacities(&mut self, capacities: &BoardCapacities) {
+        for (key, board) in self.boards.iter_mut() {
+            board.set_capacity(capacities.of(key));
+        }
+    }
+}
+
+impl From<StoredBoards> for HighScoreBoards {
+    fn from(stored: StoredBoards) -> Self {
+        let mut boards = BTreeMap::new();
+        for highscore in stored.scores {
+            boards
+                .entry(highscore.key())
+                .or_insert_with(|| HighScoreRepository::with_capacity(u8::MAX))
+                .scores
+                .push(highscore);
+        }
+        Self { boards }
+    }
+}
+
+impl From<HighScoreBoards> for StoredBoards {
+    fn from(boards: HighScoreBoards) -> Self {
+        Self {
+            scores: boards
+                .boards
+                .into_values()
+                .flat_map(|board| board.scores)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn capacities(default: u8) -> BoardCapacities {
+        BoardCapacities {
+            default,
+            difficulties: [("Hard".to_string(), 1)].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn highscores_are_kept_on_the_board_of_their_difficulty() {
+        let mut boards = HighScoreBoards::default();
+        let capacities = capacities(5);
+        boards
+            .push(HighScore::new(Score::new(10), "abc", "Normal"), &capacities)
+            .unwrap();
+        boards
+            .push(HighScore::new(Score::new(5), "def", "Hard"), &capacities)
+            .unwrap();
+
+        let normal = BoardKey::new(GameMode::Classic, "Normal");
+        let hard = BoardKey::new(GameMode::Classic, "Hard");
+        assert_eq!(boards.board(&normal).unwrap().count(), 1);
+        assert_eq!(boards.board(&hard).unwrap().count(), 1);
+        assert_eq!(boards.position(&normal, &Score::new(7)), Some(1));
+        assert_eq!(boards.position(&hard, &Score::new(3)), None);
+        assert_eq!(
+            boards.position(&BoardKey::new(GameMode::Classic, "Easy"), &Score::new(0)),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn merging_skips_highscores_already_on_the_board() {
+        let mut boards = HighScoreBoards::default();
+        let capacities = capacities(5);
+        let abc = HighScore::new(Score::new(10), "abc", "Normal");
+        boards.push(abc.clone(), &capacities).unwrap();
+
+        let def = HighScore::new(Score::new(10), "def", "Normal");
+        let merged = boards.merge([abc.clone(), def, abc], &capacities);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].name(), "def");
+        assert_eq!(boards.scores().count(), 2);
+    }
+
+    #[test]
+    fn unverified_highscores_are_flagged_then_dropped() {
+        let mut boards = HighScoreBoards::default();
+        let capacities = capacities(5);
+        boards
+            .push(HighScore::new(Score::new(10), "abc", "Normal"), &capacities)
+            .unwrap();
+        boards
+            .push(HighScore::new(Score::new(5), "def", "Hard"), &capacities)
+            .unwrap();
+
+        let flagged = boards.flag_unverified(|key, _| key.difficulty == "Normal");
+        assert_eq!(flagged, 1);
+        assert!(boards.scores().any(|h| h.name() == "def" && h.unverified()));
+        assert_eq!(
+            boards.flag_unverified(|key, _| key.difficulty == "Normal"),
+            0
+        );
+
+        assert_eq!(boards.drop_unverified(), 1);
+        assert_eq!(boards.scores().count(), 1);
+    }
+
+    #[test]
+    fn old_highscores_file_loads_onto_the_normal_board() {
+        let boards: HighScoreBoards = serde_json::from_str(
+            r#"{
+                "scores": [
+                    { "score": 10, "name": "abc", "time": "2022-01-01T00:00:00Z" },
+                    { "score": 20, "name": "def", "time": "2022-01-01T00:00:00Z" }
+                ],
+                "max_records": 10
+            }"#,
+        )
+        .unwrap();
+
+        let keys: Vec<_> = boards.keys().cloned().collect();
+        assert_eq!(keys, [BoardKey::new(GameMode::Classic, "Normal")]);
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{
+    backend::{BackendError, HighScoreBackend},
+    BoardKey, HighScore,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Highscores kept by a leaderboard server, e.g. `bevoids-leaderboard`.
+pub(crate) struct HttpBackend {
+    url: String,
+    agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+struct RankResponse {
+    rank: Option<usize>,
+}
+
+impl HttpBackend {
+    #[must_use]
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        }
+    }
+}
+
+impl HighScoreBackend for HttpBackend {
+    fn submit(&mut self, highscore: &HighScore) -> Result<Option<usize>, BackendError> {
+        let response = self
+            .agent
+            .post(&format!("{}/scores", self.url))
+            .send_json(highscore)
+            .map_err(request_error)?;
+        Ok(read_json::<RankResponse>(response)?.rank)
+    }
+
+    fn top(&mut self, key: &BoardKey, count: usize) -> Result<Vec<HighScore>, BackendError> {
+        let response = self
+            .agent
+            .get(&format!("{}/scores", self.url))
+            .query("mode", &key.mode.to_string())
+            .query("difficulty", &key.difficulty)
+            .query("count", &count.to_string())
+            .call()
+            .map_err(request_error)?;
+        read_json(response)
+    }
+
+    fn rank(&mut self, key: &BoardKey, name: &str) -> Result<Option<usize>, BackendError> {
+        let response = self
+            .agent
+            .get(&format!("{}/rank", self.url))
+            .query("mode", &key.mode.to_string())
+            .query("difficulty", &key.difficulty)
+            .query("name", name)
+            .call()
+            .map_err(request_error)?;
+        Ok(read_json::<RankResponse>(response)?.rank)
+    }
+}
+
+fn request_error(err: ureq::Error) -> BackendError {
+    match err {
+        ureq::Error::Status(status, response) => {
+            BackendError::Remote(format!("{} {}", status, response.status_text()))
+        }
+        ureq::Error::Transport(transport) => BackendError::Unreachable(transport.to_string()),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(response: ureq::Response) -> Result<T, BackendError> {
+    response
+        .into_json()
+        .map_err(|err| BackendError::Remote(format!("unexpected response: {}", err)))
+}
+
+#[cfg(test)]
+mod test {
+    use bevoids_leaderboard::{Leaderboard, Server};
+
+    use super::*;
+    use crate::bevoids::highscore::{GameMode, Score};
+
+    fn spawn_server() -> HttpBackend {
+        let server = Server::bind("127.0.0.1:0", Leaderboard::new(10)).unwrap();
+        let url = format!("http://{}", server.address());
+        server.spawn();
+        HttpBackend::new(&url)
+    }
+
+    #[test]
+    fn highscores_are_kept_by_the_server() {
+        let mut backend = spawn_server();
+        assert_eq!(
+            backend
+                .submit(&HighScore::new(Score::new(10), "Jørn Doe", "Hard"))
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            backend
+                .submit(&HighScore::new(Score::new(20), "abc", "Normal"))
+                .unwrap(),
+            Some(0)
+        );
+
+        let hard = BoardKey::new(GameMode::Classic, "Hard");
+        let top = backend.top(&hard, 5).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].name(), "Jørn Doe");
+        assert_eq!(backend.rank(&hard, "Jørn Doe").unwrap(), Some(0));
+        assert_eq!(
+            backend
+                .rank(&BoardKey::new(GameMode::Classic, "Normal"), "Jørn Doe")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn unreachable_server_is_reported_as_such() {
+        let server = Server::bind("127.0.0.1:0", Leaderboard::new(10)).unwrap();
+        let mut backend = HttpBackend::new(&format!("http://{}", server.address()));
+        drop(server);
+
+        assert!(matches!(
+            backend.top(&BoardKey::new(GameMode::Classic, "Normal"), 5),
+            Err(BackendError::Unreachable(_))
+        ));
+    }
+}
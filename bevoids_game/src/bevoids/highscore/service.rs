@@ -0,0 +1,130 @@
+use bevy::tasks::IoTaskPool;
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    backend::{BackendError, HighScoreBackend},
+    BoardKey, HighScore,
+};
+
+/// Result of a background call to the [HighScoreBackend].
+pub(crate) enum Outcome {
+    Board {
+        key: BoardKey,
+        scores: Vec<HighScore>,
+    },
+    Ranked {
+        key: BoardKey,
+        name: String,
+        rank: Option<usize>,
+    },
+    /// Number of imported highscores that made it onto the boards.
+    Imported(usize),
+    Failed(BackendError),
+}
+
+/// Calls the [HighScoreBackend] in the background, so a slow leaderboard never stalls the game.
+pub(crate) struct HighScoreService {
+    backend: Arc<Mutex<Box<dyn HighScoreBackend>>>,
+    outcomes: Arc<Mutex<Vec<Outcome>>>,
+}
+
+impl HighScoreService {
+    #[must_use]
+    pub fn new(backend: impl HighScoreBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(Mutex::new(Box::new(backend))),
+            outcomes: Default::default(),
+        }
+    }
+
+    /// Fetches the best highscores of each of the `boards`, as many as given along each.
+    pub fn refresh(&self, pool: &IoTaskPool, boards: Vec<(BoardKey, usize)>) {
+        self.run(pool, move |backend| fetch_boards(backend, boards));
+    }
+
+    /// Submits `highscore`, then fetches the `count` best highscores of its board along with the
+    /// rank of the player.
+    pub fn submit(&self, pool: &IoTaskPool, highscore: HighScore, count: usize) {
+        self.run(pool, move |backend| {
+            let key = highscore.key();
+            let result = backend.submit(&highscore).and_then(|_| {
+                let scores = backend.top(&key, count)?;
+                let rank = backend.rank(&key, highscore.name())?;
+                Ok((scores, rank))
+            });
+            match result {
+                Ok((scores, rank)) => vec![
+                    Outcome::Board {
+                        key: key.clone(),
+                        scores,
+                    },
+                    Outcome::Ranked {
+                        key,
+                        name: highscore.name().clone(),
+                        rank,
+                    },
+                ],
+                Err(err) => vec![Outcome::Failed(err)],
+            }
+        });
+    }
+
+    /// Imports the `highscores`, then fetches the best highscores of each of the `boards`, as
+    /// many as given along each.
+    pub fn import(
+        &self,
+        pool: &IoTaskPool,
+        highscores: Vec<HighScore>,
+        boards: Vec<(BoardKey, usize)>,
+    ) {
+        self.run(pool, move |backend| match backend.import(&highscores) {
+            Ok(imported) => {
+                let mut outcomes = vec![Outcome::Imported(imported)];
+                outcomes.extend(fetch_boards(backend, boards));
+                outcomes
+            }
+            Err(err) => vec![Outcome::Failed(err)],
+        });
+    }
+
+    /// Outcomes of the calls finished since last time.
+    pub fn take_outcomes(&self) -> Vec<Outcome> {
+        mem::take(&mut *self.outcomes.lock().unwrap())
+    }
+
+    fn run(
+        &self,
+        pool: &IoTaskPool,
+        call: impl FnOnce(&mut dyn HighScoreBackend) -> Vec<Outcome> + Send + 'static,
+    ) {
+        let backend = self.backend.clone();
+        let outcomes = self.outcomes.clone();
+        pool.spawn(async move {
+            let outcome = call(backend.lock().unwrap().as_mut());
+            outcomes.lock().unwrap().extend(outcome);
+        })
+        .detach();
+    }
+}
+
+/// The best highscores of each of the `boards`, as many as given along each.
+fn fetch_boards(
+    backend: &mut dyn HighScoreBackend,
+    boards: Vec<(BoardKey, usize)>,
+) -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+    for (key, count) in boards {
+        match backend.top(&key, count) {
+            Ok(scores) => outcomes.push(Outcome::Board { key, scores }),
+            Err(err) => {
+                // no need to fail for every board in turn
+                outcomes.push(Outcome::Failed(err));
+                break;
+            }
+        }
+    }
+    outcomes
+}
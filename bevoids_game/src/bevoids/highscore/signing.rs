@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::{fs, io, path::Path};
+
+use crate::bevoids::{files::write_atomically, stats::RunStats};
+
+use super::{
+    storage::{io_error, StorageError},
+    BoardKey, GameMode, HighScore, Score,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const KEY_LEN: usize = 32;
+
+/// Key of this install, signing each highscore board so editing the highscores file by hand is
+/// detected.
+pub(crate) struct SigningKey([u8; KEY_LEN]);
+
+/// The parts of a highscore covered by its signature - all of it.
+#[derive(Serialize)]
+struct Signed<'a> {
+    mode: GameMode,
+    difficulty: &'a str,
+    score: Score,
+    name: &'a str,
+    time: &'a DateTime<Utc>,
+    stats: Option<&'a RunStats>,
+    unverified: bool,
+}
+
+impl<'a> From<&'a HighScore> for Signed<'a> {
+    fn from(highscore: &'a HighScore) -> Self {
+        Self {
+            mode: highscore.mode,
+            difficulty: &highscore.difficulty,
+            score: highscore.score,
+            name: &highscore.name,
+            time: &highscore.time,
+            stats: highscore.stats.as_ref(),
+            unverified: highscore.unverified,
+        }
+    }
+}
+
+impl SigningKey {
+    #[must_use]
+    pub fn generate() -> Self {
+        Self(rand::random())
+    }
+
+    /// Reads the key kept in `path`, if one has been created yet.
+    pub fn load(path: &Path) -> Result<Option<Self>, StorageError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(io_error(path, source)),
+        };
+        let mut key = [0; KEY_LEN];
+        hex::decode_to_slice(content.trim(), &mut key)
+            .map_err(|err| io_error(path, io::Error::new(io::ErrorKind::InvalidData, err)))?;
+        Ok(Some(Self(key)))
+    }
+
+    /// Reads the key kept in `path`, generating and saving a new one if there is none.
+    pub fn load_or_create(path: &Path) -> Result<Self, StorageError> {
+        match Self::load(path)? {
+            Some(key) => Ok(key),
+            None => {
+                let key = Self::generate();
+                write_atomically(path, hex::encode(key.0))
+                    .map_err(|source| io_error(path, source))?;
+                Ok(key)
+            }
+        }
+    }
+
+    /// Signature of the `highscores` on the board `key`.
+    pub fn sign(&self, key: &BoardKey, highscores: &[HighScore]) -> String {
+        hex::encode(self.mac(key, highscores).finalize().into_bytes())
+    }
+
+    /// Whether `signature` is that of the `highscores` on the board `key`.
+    pub fn verify(&self, key: &BoardKey, highscores: &[HighScore], signature: &str) -> bool {
+        hex::decode(signature)
+            .ok()
+            .filter(|signature| self.mac(key, highscores).verify_slice(signature).is_ok())
+            .is_some()
+    }
+
+    fn mac(&self, key: &BoardKey, highscores: &[HighScore]) -> HmacSha256 {
+        let signed: Vec<Signed> = highscores.iter().map(Signed::from).collect();
+        let canonical =
+            serde_json::to_vec(&(key.to_string(), signed)).expect("highscores are serializable");
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC takes keys of any size");
+        mac.update(&canonical);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+
+    fn normal() -> BoardKey {
+        BoardKey::new(GameMode::Classic, "Normal")
+    }
+
+    fn highscores(scores: &[(u32, String)]) -> Vec<HighScore> {
+        scores
+            .iter()
+            .map(|(score, name)| HighScore::new(Score::new(*score), name.as_str(), "Normal"))
+            .collect()
+    }
+
+    #[quickcheck]
+    fn qc_signed_highscores_verify(scores: Vec<(u32, String)>) -> bool {
+        let key = SigningKey::generate();
+        let highscores = highscores(&scores);
+        let signature = key.sign(&normal(), &highscores);
+        key.verify(&normal(), &highscores, &signature)
+    }
+
+    #[quickcheck]
+    fn qc_tampered_highscores_fail_to_verify(
+        scores: Vec<(u32, String)>,
+        index: usize,
+        bonus: u32,
+    ) -> TestResult {
+        if scores.is_empty() || bonus == 0 {
+            return TestResult::discard();
+        }
+
+        let key = SigningKey::generate();
+        let mut highscores = highscores(&scores);
+        let signature = key.sign(&normal(), &highscores);
+
+        let tampered = &mut highscores[index % scores.len()];
+        tampered.score = Score::new(u32::from(tampered.score).wrapping_add(bonus));
+        TestResult::from_bool(!key.verify(&normal(), &highscores, &signature))
+    }
+
+    #[test]
+    fn highscores_fail_to_verify_on_another_board_or_key() {
+        let key = SigningKey::generate();
+        let highscores = highscores(&[(10, "abc".to_string())]);
+        let signature = key.sign(&normal(), &highscores);
+
+        assert!(!key.verify(
+            &BoardKey::new(GameMode::Classic, "Hard"),
+            &highscores,
+            &signature
+        ));
+        assert!(!SigningKey::generate().verify(&normal(), &highscores, &signature));
+        assert!(!key.verify(&normal(), &highscores, "not hex"));
+    }
+}
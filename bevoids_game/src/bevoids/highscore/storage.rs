@@ -0,0 +1,367 @@
+use bevy::log;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::bevoids::{
+    dirs::project_dirs,
+    files::{move_to_backup, with_suffix, write_atomically},
+};
+
+use super::{signing::SigningKey, HighScoreBoards};
+
+const HIGHSCORES_FILE: &str = "highscores.json";
+const STATISTICS_FILE: &str = "statistics.json";
+
+/// Errors encountered while reading or writing the highscores.
+#[derive(Debug, Display)]
+pub(crate) enum StorageError {
+    #[display(fmt = "no data directory to keep the highscores in")]
+    NoDataDir,
+    #[display(fmt = "unable to access {}: {}", "path.display()", source)]
+    Io { path: PathBuf, source: io::Error },
+    #[display(
+        fmt = "{} was corrupt ({}), and has been moved to {}",
+        "path.display()",
+        source,
+        "backup.display()"
+    )]
+    Corrupt {
+        path: PathBuf,
+        backup: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl std::error::Error for StorageError {}
+
+/// The highscores, along with the signature of each board by the [SigningKey] of this install.
+#[derive(Serialize, Deserialize)]
+struct SignedBoards {
+    #[serde(flatten)]
+    boards: HighScoreBoards,
+    #[serde(default)]
+    signatures: BTreeMap<String, String>,
+}
+
+/// Location of the file holding the highscores.
+#[derive(Debug, Clone)]
+pub(crate) struct HighScoresFile(pub PathBuf);
+
+impl HighScoresFile {
+    /// The highscores file in the users data directory.
+    pub fn locate() -> Result<Self, StorageError> {
+        project_dirs()
+            .map(|dirs| Self(dirs.data_dir().join(HIGHSCORES_FILE)))
+            .ok_or(StorageError::NoDataDir)
+    }
+
+    /// File holding the highscores waiting to be uploaded to a leaderboard.
+    pub fn upload_queue(&self) -> PathBuf {
+        with_suffix(&self.0, ".queue")
+    }
+
+    /// File holding the key signing the highscores, e.g. `highscores.key` in the data directory.
+    pub fn key_file(&self) -> PathBuf {
+        self.0.with_extension("key")
+    }
+
+    /// File recording that highscores have been signed on this install, e.g. `highscores.signed`
+    /// - from then on, highscores without signatures are never taken for ones saved before.
+    pub fn signing_record(&self) -> PathBuf {
+        self.0.with_extension("signed")
+    }
+
+    /// File holding the lifetime statistics, `statistics.json` next to the highscores.
+    pub fn statistics_file(&self) -> PathBuf {
+        self.0.with_file_name(STATISTICS_FILE)
+    }
+
+    /// Reads the highscores, if any has been saved yet.
+    ///
+    /// A file that cannot be parsed is moved aside to a timestamped backup, rather than
+    /// being overwritten by the next save. Highscores on boards that fail to verify against
+    /// their signature are flagged as unverified.
+    pub fn load(&self) -> Result<Option<HighScoreBoards>, StorageError> {
+        let path = &self.0;
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(io_error(path, source)),
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(signed) => Ok(Some(self.verify(signed, is_baseline(&content))?)),
+            Err(source) => {
+                let backup = move_to_backup(path).map_err(|source| io_error(path, source))?;
+                Err(StorageError::Corrupt {
+                    path: path.clone(),
+                    backup,
+                    source,
+                })
+            }
+        }
+    }
+
+    /// Flags the highscores on boards failing to verify, trusting unsigned ones only when
+    /// upgrading a `baseline` file on an install that has never signed any highscores.
+    fn verify(
+        &self,
+        signed: SignedBoards,
+        baseline: bool,
+    ) -> Result<HighScoreBoards, StorageError> {
+        let SignedBoards {
+            mut boards,
+            signatures,
+        } = signed;
+        let flagged = match SigningKey::load(&self.key_file())? {
+            Some(key) => boards.flag_unverified(|board, highscores| {
+                signatures
+                    .get(&board.to_string())
+                    .filter(|signature| key.verify(board, highscores, signature))
+                    .is_some()
+            }),
+            // saved before highscores were signed
+            None if baseline && !self.signing_record().exists() => 0,
+            None => boards.flag_unverified(|_, _| false),
+        };
+        if flagged > 0 {
+            log::warn!(path = ?self.0, "{} highscores failed to verify", flagged);
+        }
+        Ok(boards)
+    }
+
+    /// Writes the highscores to a temporary file, then moves it in place of the old one -
+    /// a failed write never leaves a half written highscores file behind.
+    pub fn save(&self, highscores: &HighScoreBoards) -> Result<(), StorageError> {
+        let path = &self.0;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|source| io_error(dir, source))?;
+        }
+
+        let key = SigningKey::load_or_create(&self.key_file())?;
+        let record = self.signing_record();
+        if !record.exists() {
+            fs::write(&record, "").map_err(|source| io_error(&record, source))?;
+        }
+        let signed = SignedBoards {
+            boards: highscores.clone(),
+            signatures: highscores
+                .keys()
+                .map(|board| {
+                    let scores = &highscores.board(board).expect("board exists").scores;
+                    (board.to_string(), key.sign(board, scores))
+                })
+                .collect(),
+        };
+
+        let content = serde_json::to_string_pretty(&signed).expect("highscores are serializable");
+        write_atomically(path, content).map_err(|source| io_error(path, source))
+    }
+}
+
+/// Whether `content` is a highscores file exactly as saved before highscores were signed - a
+/// single list of scores along with `max_records`, each score with nothing but its value, name
+/// and time.
+fn is_baseline(content: &str) -> bool {
+    const BASELINE_FIELDS: [&str; 3] = ["score", "name", "time"];
+    let file = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Object(file)) => file,
+        _ => return false,
+    };
+    let scores = match file.get("scores").and_then(Value::as_array) {
+        Some(scores) => scores,
+        None => return false,
+    };
+    file.len() == 2
+        && file.contains_key("max_records")
+        && scores.iter().all(|highscore| {
+            matches!(highscore.as_object(), Some(fields)
+                if fields.keys().all(|field| BASELINE_FIELDS.contains(&field.as_str())))
+        })
+}
+
+pub(super) fn io_error(path: &Path, source: io::Error) -> StorageError {
+    StorageError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::{
+        files::TestDir,
+        highscore::{BoardCapacities, HighScore, Score},
+        settings::Settings,
+        stats::RunStats,
+    };
+    use std::time::Duration;
+
+    fn temp_file(dir: &TestDir) -> HighScoresFile {
+        HighScoresFile(dir.join(HIGHSCORES_FILE))
+    }
+
+    fn unverified(file: &HighScoresFile) -> Vec<bool> {
+        let boards = file.load().unwrap().unwrap();
+        boards.scores().map(HighScore::unverified).collect()
+    }
+
+    #[test]
+    fn missing_file_is_no_highscores() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        assert!(matches!(file.load(), Ok(None)));
+    }
+
+    #[test]
+    fn saved_highscores_are_loaded() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        file.save(&HighScoreBoards::default()).unwrap();
+
+        let loaded = file.load().unwrap().unwrap();
+        assert_eq!(loaded.keys().count(), 0);
+        assert!(!with_suffix(&file.0, ".tmp").exists());
+    }
+
+    #[test]
+    fn edited_highscores_are_flagged() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        let capacities = BoardCapacities::from(&Settings::default());
+        let mut boards = HighScoreBoards::default();
+        boards
+            .push(HighScore::new(Score::new(10), "abc", "Normal"), &capacities)
+            .unwrap();
+        file.save(&boards).unwrap();
+        assert_eq!(unverified(&file), [false]);
+
+        let content = fs::read_to_string(&file.0).unwrap();
+        let edited = content.replace("\"score\": 10", "\"score\": 99999");
+        fs::write(&file.0, edited).unwrap();
+        assert_eq!(unverified(&file), [true]);
+
+        // flagged highscores stay flagged, even once signed again
+        file.save(&file.load().unwrap().unwrap()).unwrap();
+        assert_eq!(unverified(&file), [true]);
+    }
+
+    #[test]
+    fn highscores_with_stats_verify_until_edited() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        let capacities = BoardCapacities::from(&Settings::default());
+        let stats = RunStats {
+            duration: Duration::new(123, 456_789_123),
+            asteroids_shot: 3,
+            asteroids_spawned: 7,
+            lasers_fired: 11,
+            max_speed: 0.1 + 0.2,
+        };
+        let mut boards = HighScoreBoards::default();
+        boards
+            .push(
+                HighScore::new(Score::new(10), "abc", "Normal").with_stats(stats),
+                &capacities,
+            )
+            .unwrap();
+        file.save(&boards).unwrap();
+        assert_eq!(unverified(&file), [false]);
+
+        let content = fs::read_to_string(&file.0).unwrap();
+        let edited = content.replace("\"lasers_fired\": 11", "\"lasers_fired\": 3");
+        fs::write(&file.0, &edited).unwrap();
+        assert_eq!(unverified(&file), [true]);
+
+        // nor does it pass as saved before signing, once the key and signatures are gone
+        let mut stripped: serde_json::Value = serde_json::from_str(&content).unwrap();
+        stripped.as_object_mut().unwrap().remove("signatures");
+        fs::write(&file.0, stripped.to_string()).unwrap();
+        fs::remove_file(file.key_file()).unwrap();
+        assert_eq!(unverified(&file), [true]);
+    }
+
+    /// Highscores file as saved before highscores were signed.
+    const BASELINE: &str = r#"{
+        "scores": [{ "score": 10, "name": "abc", "time": "2022-01-01T00:00:00Z" }],
+        "max_records": 10
+    }"#;
+
+    #[test]
+    fn baseline_highscores_verify_only_before_signing() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        fs::write(&file.0, BASELINE).unwrap();
+        assert_eq!(unverified(&file), [false]);
+
+        SigningKey::load_or_create(&file.key_file()).unwrap();
+        assert_eq!(unverified(&file), [true]);
+    }
+
+    #[test]
+    fn unsigned_highscores_are_flagged_once_signing_started() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        let capacities = BoardCapacities::from(&Settings::default());
+        let mut boards = HighScoreBoards::default();
+        boards
+            .push(HighScore::new(Score::new(10), "abc", "Normal"), &capacities)
+            .unwrap();
+        file.save(&boards).unwrap();
+
+        // without the key, the signatures and the statistics, edited
+        let mut stripped: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file.0).unwrap()).unwrap();
+        stripped.as_object_mut().unwrap().remove("signatures");
+        stripped["scores"][0]["score"] = 99999.into();
+        fs::write(&file.0, stripped.to_string()).unwrap();
+        fs::remove_file(file.key_file()).unwrap();
+        assert_eq!(unverified(&file), [true]);
+
+        // even when made up to look like it was saved before signing
+        fs::write(&file.0, BASELINE).unwrap();
+        assert_eq!(unverified(&file), [true]);
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_to_backup() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        fs::write(&file.0, "{ not json").unwrap();
+
+        match file.load() {
+            Err(StorageError::Corrupt { backup, .. }) => {
+                assert!(!file.0.exists());
+                assert_eq!(fs::read_to_string(backup).unwrap(), "{ not json");
+            }
+            other => panic!("expected a corrupt file, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn corrupt_files_in_a_row_keep_their_backups() {
+        let dir = TestDir::new();
+        let file = temp_file(&dir);
+        let backups: Vec<PathBuf> = ["{ first", "{ second"]
+            .iter()
+            .map(|content| {
+                fs::write(&file.0, content).unwrap();
+                match file.load() {
+                    Err(StorageError::Corrupt { backup, .. }) => backup,
+                    other => panic!("expected a corrupt file, got {:?}", other),
+                }
+            })
+            .collect();
+
+        assert_ne!(backups[0], backups[1]);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{ first");
+        assert_eq!(fs::read_to_string(&backups[1]).unwrap(), "{ second");
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use derive_more::{Display, From};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::bevoids::{settings::UnverifiedHighScores, stats::RunStats};
+
+use super::{
+    BoardCapacities, GameMode, HighScore, HighScoreBoards, HighScoresFile, Score, StorageError,
+};
+
+const DEFAULT_FILE: &str = "bevoids-highscores.csv";
+
+/// Errors encountered while exporting or importing highscores.
+#[derive(Debug, Display, From)]
+pub(crate) enum TransferError {
+    #[display(fmt = "{}", _0)]
+    Storage(StorageError),
+    #[display(fmt = "{}: expected a .csv or .json file", "path.display()")]
+    #[from(ignore)]
+    UnknownFormat { path: PathBuf },
+    #[display(fmt = "unable to access {}: {}", "path.display()", source)]
+    #[from(ignore)]
+    Io { path: PathBuf, source: io::Error },
+    #[display(fmt = "{}: {}", "path.display()", source)]
+    #[from(ignore)]
+    Csv { path: PathBuf, source: csv::Error },
+    #[display(fmt = "{}: {}", "path.display()", source)]
+    #[from(ignore)]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl std::error::Error for TransferError {}
+
+/// Formats highscores are exported in, and imported from - told apart by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn of(path: &Path) -> Result<Self, TransferError> {
+        match path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Ok(Format::Csv),
+            Some("json") => Ok(Format::Json),
+            _ => Err(TransferError::UnknownFormat {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+}
+
+/// A highscore as a single CSV row, the statistics of the run left empty if not recorded.
+#[derive(Serialize, Deserialize)]
+struct CsvRecord {
+    mode: GameMode,
+    difficulty: String,
+    score: u32,
+    name: String,
+    time: DateTime<Utc>,
+    duration: Option<f64>,
+    asteroids_shot: Option<usize>,
+    asteroids_spawned: Option<usize>,
+    lasers_fired: Option<usize>,
+    max_speed: Option<f32>,
+    #[serde(default)]
+    unverified: bool,
+}
+
+impl From<&HighScore> for CsvRecord {
+    fn from(highscore: &HighScore) -> Self {
+        let stats = highscore.stats.as_ref();
+        Self {
+            mode: highscore.mode,
+            difficulty: highscore.difficulty.clone(),
+            score: highscore.score.into(),
+            name: highscore.name.clone(),
+            time: highscore.time,
+            duration: stats.map(|stats| stats.duration.as_secs_f64()),
+            asteroids_shot: stats.map(|stats| stats.asteroids_shot),
+            asteroids_spawned: stats.map(|stats| stats.asteroids_spawned),
+            lasers_fired: stats.map(|stats| stats.lasers_fired),
+            max_speed: stats.map(|stats| stats.max_speed),
+            unverified: highscore.unverified,
+        }
+    }
+}
+
+impl From<CsvRecord> for HighScore {
+    fn from(record: CsvRecord) -> Self {
+        let stats = record.duration.map(|duration| RunStats {
+            duration: Duration::from_secs_f64(duration.max(0.)),
+            asteroids_shot: record.asteroids_shot.unwrap_or_default(),
+            asteroids_spawned: record.asteroids_spawned.unwrap_or_default(),
+            lasers_fired: record.lasers_fired.unwrap_or_default(),
+            max_speed: record.max_speed.unwrap_or_default(),
+        });
+        Self {
+            score: Score::new(record.score),
+            name: record.name,
+            time: record.time,
+            mode: record.mode,
+            difficulty: record.difficulty,
+            stats,
+            unverified: record.unverified,
+        }
+    }
+}
+
+/// Writes the highscores of every board to `path`, as CSV or JSON depending on its extension.
+///
+/// Returns the number of highscores written.
+pub(crate) fn export(boards: &HighScoreBoards, path: &Path) -> Result<usize, TransferError> {
+    match Format::of(path)? {
+        Format::Csv => {
+            let csv_error = |source| TransferError::Csv {
+                path: path.to_path_buf(),
+                source,
+            };
+            let mut writer = csv::Writer::from_path(path).map_err(csv_error)?;
+            for highscore in boards.scores() {
+                writer
+                    .serialize(CsvRecord::from(highscore))
+                    .map_err(csv_error)?;
+            }
+            writer.flush().map_err(|source| TransferError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        }
+        Format::Json => {
+            let content =
+                serde_json::to_string_pretty(boards).expect("highscores are serializable");
+            fs::write(path, content).map_err(|source| TransferError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        }
+    }
+    Ok(boards.scores().count())
+}
+
+/// Reads the highscores exported to `path`, as CSV or JSON depending on its extension.
+///
+/// JSON files may also be a `highscores.json` copied from another machine. Nothing vouches
+/// for highscores coming from outside the highscores file, so all of them are flagged as
+/// unverified.
+pub(crate) fn import(path: &Path) -> Result<Vec<HighScore>, TransferError> {
+    let mut highscores: Vec<HighScore> = match Format::of(path)? {
+        Format::Csv => csv::Reader::from_path(path)
+            .and_then(|mut reader| {
+                reader
+                    .deserialize::<CsvRecord>()
+                    .map(|record| record.map(HighScore::from))
+                    .collect::<Result<_, _>>()
+            })
+            .map_err(|source| TransferError::Csv {
+                path: path.to_path_buf(),
+                source,
+            })?,
+        Format::Json => {
+            let content = fs::read_to_string(path).map_err(|source| TransferError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            let boards: HighScoreBoards =
+                serde_json::from_str(&content).map_err(|source| TransferError::Json {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            boards.scores().cloned().collect()
+        }
+    };
+    for highscore in &mut highscores {
+        highscore.unverified = true;
+    }
+    Ok(highscores)
+}
+
+/// Where highscores are exported to and imported from, unless told otherwise - in the users
+/// documents directory.
+pub(crate) fn default_path() -> PathBuf {
+    directories::UserDirs::new()
+        .map(|dirs| {
+            dirs.document_dir()
+                .unwrap_or_else(|| dirs.home_dir())
+                .join(DEFAULT_FILE)
+        })
+        .unwrap_or_else(|| DEFAULT_FILE.into())
+}
+
+/// Exports the highscores kept in `file` to `path`, returning the number exported.
+pub(crate) fn export_file(file: &HighScoresFile, path: &Path) -> Result<usize, TransferError> {
+    export(&file.load()?.unwrap_or_default(), path)
+}
+
+/// Merges the highscores exported to `path` into those kept in `file`, returning the number
+/// that made it onto the boards.
+pub(crate) fn import_file(
+    file: &HighScoresFile,
+    capacities: &BoardCapacities,
+    unverified: UnverifiedHighScores,
+    path: &Path,
+) -> Result<usize, TransferError> {
+    let mut highscores = import(path)?;
+    let mut boards = file.load()?.unwrap_or_default();
+    if unverified == UnverifiedHighScores::Drop {
+        boards.drop_unverified();
+        highscores.retain(|highscore| !highscore.unverified);
+    }
+    boards.set_capacities(capacities);
+    let merged = boards.merge(highscores, capacities);
+    if !merged.is_empty() {
+        file.save(&boards)?;
+    }
+    Ok(merged.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::{files::TestDir, highscore::BoardKey, settings::Settings};
+
+    fn boards() -> HighScoreBoards {
+        let capacities = BoardCapacities::from(&Settings::default());
+        let mut boards = HighScoreBoards::default();
+        let stats = RunStats {
+            duration: Duration::from_millis(61500),
+            asteroids_shot: 3,
+            lasers_fired: 4,
+            ..RunStats::default()
+        };
+        boards
+            .push(
+                HighScore::new(Score::new(10), "Doe, \"Jørn\"", "Normal").with_stats(stats),
+                &capacities,
+            )
+            .unwrap();
+        boards
+            .push(HighScore::new(Score::new(5), "abc", "Hard"), &capacities)
+            .unwrap();
+        boards
+    }
+
+    fn round_trip(extension: &str) {
+        let dir = TestDir::new();
+        let path = dir.join(&format!("highscores.{}", extension));
+        let boards = boards();
+        assert_eq!(export(&boards, &path).unwrap(), 2);
+        let imported = import(&path).unwrap();
+
+        let mut merged = boards.clone();
+        let capacities = BoardCapacities::from(&Settings::default());
+        assert!(merged.merge(imported.clone(), &capacities).is_empty());
+
+        let doe = imported
+            .iter()
+            .find(|h| h.score() == Score::new(10))
+            .unwrap();
+        assert_eq!(doe.name(), "Doe, \"Jørn\"");
+        assert_eq!(doe.key(), BoardKey::new(GameMode::Classic, "Normal"));
+        assert_eq!(doe.stats().unwrap().asteroids_shot, 3);
+        let abc = imported
+            .iter()
+            .find(|h| h.score() == Score::new(5))
+            .unwrap();
+        assert_eq!(abc.stats(), None);
+    }
+
+    #[test]
+    fn csv_export_imports_as_the_same_highscores() {
+        round_trip("csv");
+    }
+
+    #[test]
+    fn json_export_imports_as_the_same_highscores() {
+        round_trip("json");
+    }
+
+    #[test]
+    fn edited_csv_imports_as_unverified() {
+        let dir = TestDir::new();
+        let path = dir.join("edited.csv");
+        export(&boards(), &path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace(",10,", ",99999,")).unwrap();
+
+        let file = HighScoresFile(dir.join("highscores.json"));
+        let capacities = BoardCapacities::from(&Settings::default());
+        let imported = import_file(&file, &capacities, UnverifiedHighScores::Flag, &path).unwrap();
+        assert_eq!(imported, 2);
+
+        let boards = file.load().unwrap().unwrap();
+        let edited = boards
+            .scores()
+            .find(|h| h.score() == Score::new(99999))
+            .unwrap();
+        assert!(edited.unverified());
+        assert!(boards.scores().all(HighScore::unverified));
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        assert!(matches!(
+            export(&boards(), Path::new("highscores.txt")),
+            Err(TransferError::UnknownFormat { .. })
+        ));
+    }
+}
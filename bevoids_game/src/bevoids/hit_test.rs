@@ -6,9 +6,13 @@ use crate::bounds::GfxBounds;
 
 use super::{
     asteroids::{Asteroid, AsteroidExplosionEvent, AsteroidShotEvent},
-    laser::Laser,
+    hyperspace::InHyperspace,
+    laser::{EnemyLaser, Laser},
     movement::InsideWindow,
-    player::{Player, PlayerDeadEvent},
+    pickup::{Pickup, PickupCollectedEvent},
+    player::{Invulnerable, Player, PlayerDeadEvent},
+    saucer::{Saucer, SaucerExplosionEvent, SaucerShotEvent},
+    shield::Shield,
 };
 
 pub(crate) fn laser_vs_asteroid_system(
@@ -31,10 +35,19 @@ pub(crate) fn laser_vs_asteroid_system(
 }
 
 pub(crate) fn player_vs_asteroid_system(
-    player_query: Query<&GfxBounds, (With<Player>, With<InsideWindow>)>,
+    player_query: Query<
+        &GfxBounds,
+        (
+            With<Player>,
+            With<InsideWindow>,
+            Without<Invulnerable>,
+            Without<InHyperspace>,
+        ),
+    >,
     asteroids_query: Query<(Entity, &GfxBounds), (With<Asteroid>, With<InsideWindow>)>,
     mut player_dead_event: EventWriter<PlayerDeadEvent>,
     mut remove_asteroid_event: EventWriter<AsteroidExplosionEvent>,
+    shield: Res<Shield>,
 ) {
     'player: for player_bounds in player_query.iter() {
         let player_sphere = player_bounds.as_sphere();
@@ -42,10 +55,121 @@ pub(crate) fn player_vs_asteroid_system(
         for (asteroid, asteroid_bounds) in asteroids_query.iter() {
             let asteroid_sphere = asteroid_bounds.as_sphere();
             if player_sphere.intersects(asteroid_sphere) {
-                player_dead_event.send(PlayerDeadEvent);
+                // the shield absorbs the collision, only destroying the asteroid
+                if shield.is_up() {
+                    log::debug!(?asteroid, "shield hit asteroid");
+                } else {
+                    player_dead_event.send(PlayerDeadEvent);
+                }
                 remove_asteroid_event.send(AsteroidExplosionEvent::new(asteroid));
                 continue 'player;
             }
         }
     }
 }
+
+pub(crate) fn laser_vs_saucer_system(
+    mut commands: Commands,
+    laser_query: Query<(Entity, &GfxBounds), With<Laser>>,
+    saucer_query: Query<(Entity, &GfxBounds), (With<Saucer>, With<InsideWindow>)>,
+    mut saucer_shot_event: EventWriter<SaucerShotEvent>,
+) {
+    'laser: for (laser_entity, laser_bounds) in laser_query.iter() {
+        for (saucer, saucer_bounds) in saucer_query.iter() {
+            if laser_bounds
+                .as_sphere()
+                .intersects(saucer_bounds.as_sphere())
+            {
+                log::debug!(?saucer, "laser hit saucer");
+                saucer_shot_event.send(SaucerShotEvent::new(saucer));
+                commands.entity(laser_entity).insert(Despawn);
+                continue 'laser;
+            }
+        }
+    }
+}
+
+pub(crate) fn enemy_laser_vs_player_system(
+    mut commands: Commands,
+    player_query: Query<
+        &GfxBounds,
+        (
+            With<Player>,
+            With<InsideWindow>,
+            Without<Invulnerable>,
+            Without<InHyperspace>,
+        ),
+    >,
+    laser_query: Query<(Entity, &GfxBounds), With<EnemyLaser>>,
+    mut player_dead_event: EventWriter<PlayerDeadEvent>,
+    shield: Res<Shield>,
+) {
+    'laser: for (laser_entity, laser_bounds) in laser_query.iter() {
+        for player_bounds in player_query.iter() {
+            if laser_bounds
+                .as_sphere()
+                .intersects(player_bounds.as_sphere())
+            {
+                // the shield absorbs the shot
+                if shield.is_up() {
+                    log::debug!(laser=?laser_entity, "shield hit by enemy laser");
+                } else {
+                    player_dead_event.send(PlayerDeadEvent);
+                }
+                commands.entity(laser_entity).insert(Despawn);
+                continue 'laser;
+            }
+        }
+    }
+}
+
+pub(crate) fn player_vs_saucer_system(
+    player_query: Query<
+        &GfxBounds,
+        (
+            With<Player>,
+            With<InsideWindow>,
+            Without<Invulnerable>,
+            Without<InHyperspace>,
+        ),
+    >,
+    saucer_query: Query<(Entity, &GfxBounds), (With<Saucer>, With<InsideWindow>)>,
+    mut player_dead_event: EventWriter<PlayerDeadEvent>,
+    mut remove_saucer_event: EventWriter<SaucerExplosionEvent>,
+    shield: Res<Shield>,
+) {
+    'player: for player_bounds in player_query.iter() {
+        for (saucer, saucer_bounds) in saucer_query.iter() {
+            if player_bounds
+                .as_sphere()
+                .intersects(saucer_bounds.as_sphere())
+            {
+                // the shield absorbs the collision, only destroying the saucer
+                if shield.is_up() {
+                    log::debug!(?saucer, "shield hit saucer");
+                } else {
+                    player_dead_event.send(PlayerDeadEvent);
+                }
+                remove_saucer_event.send(SaucerExplosionEvent::new(saucer));
+                continue 'player;
+            }
+        }
+    }
+}
+
+pub(crate) fn player_vs_pickup_system(
+    player_query: Query<&GfxBounds, (With<Player>, With<InsideWindow>, Without<InHyperspace>)>,
+    pickup_query: Query<(Entity, &GfxBounds), With<Pickup>>,
+    mut pickup_collected_event: EventWriter<PickupCollectedEvent>,
+) {
+    for player_bounds in player_query.iter() {
+        let player_sphere = player_bounds.as_sphere();
+
+        for (pickup, pickup_bounds) in pickup_query.iter() {
+            if player_sphere.intersects(pickup_bounds.as_sphere()) {
+                log::debug!(?pickup, "player hit pickup");
+                pickup_collected_event.send(PickupCollectedEvent::new(pickup));
+            }
+        }
+    }
+}
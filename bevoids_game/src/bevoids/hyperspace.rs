@@ -0,0 +1,204 @@
+use bevoids_assets::SoundAsset;
+use bevy::{log, prelude::*};
+use bevy_effects::sound::{SfxCmdEvent, StopSfx};
+use rand::Rng;
+
+use crate::bounds::GfxBounds;
+
+use super::{
+    asteroids::Asteroid,
+    input::{ActionState, InputAction},
+    movement::{ShadowController, ShadowOf},
+    player::{random_2d_position_away_from, Flame, Player, PlayerDeadEvent},
+    rng::GameRng,
+    settings::Settings,
+};
+
+/// Time left before the next hyperspace jump is possible, if any.
+#[derive(Default)]
+pub(crate) struct HyperspaceCooldown(Option<Timer>);
+
+/// A player jumping through hyperspace, fading out where it was and in where it arrives.
+#[derive(Debug, Component)]
+pub(crate) struct Hyperspace {
+    timer: Timer,
+    arrived: bool,
+}
+
+/// Marks the jumping player, along with its shadows, as out of reach of anything - be it
+/// asteroids, saucers, pickups or the controls.
+#[derive(Debug, Component)]
+pub(crate) struct InHyperspace;
+
+impl Hyperspace {
+    #[must_use]
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            timer: Timer::new(settings.player.hyperspace_fade, false),
+            arrived: false,
+        }
+    }
+
+    /// Opacity of the player, from fully shown to gone while leaving, and back while arriving.
+    pub fn alpha(&self) -> f32 {
+        if self.arrived {
+            self.timer.percent()
+        } else {
+            self.timer.percent_left()
+        }
+    }
+}
+
+pub(crate) fn reset_hyperspace_cooldown_system(mut commands: Commands) {
+    commands.insert_resource(HyperspaceCooldown::default());
+}
+
+pub(crate) fn hyperspace_jump_system(
+    mut commands: Commands,
+    mut cooldown: ResMut<HyperspaceCooldown>,
+    player_query: Query<Entity, (With<Player>, With<ShadowController>, Without<Hyperspace>)>,
+    shadow_query: Query<(Entity, &ShadowOf)>,
+    flame_query: Query<Entity, With<Flame>>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let ready = match cooldown.0.as_mut() {
+        Some(timer) => timer.tick(time.delta()).finished(),
+        None => true,
+    };
+    if !(ready && actions.just_pressed(InputAction::Hyperspace)) {
+        return;
+    }
+
+    if let Some(player) = player_query.iter().next() {
+        log::info!(?player, "hyperspace jump");
+        commands
+            .entity(player)
+            .insert(Hyperspace::new(&settings))
+            .insert(InHyperspace);
+        for (shadow, _) in shadow_query
+            .iter()
+            .filter(|(_, shadowof)| shadowof.controller == player)
+        {
+            commands.entity(shadow).insert(InHyperspace);
+        }
+
+        // the thruster cuts out, until accelerating again once arrived
+        sfx_event.send(StopSfx::new(SoundAsset::Thruster).into());
+        for flame in flame_query.iter() {
+            commands.entity(flame).despawn();
+        }
+        cooldown.0 = Some(Timer::new(settings.player.hyperspace_cooldown, false));
+    }
+}
+
+/// Fades the jumping player - and its shadows - out, moves it away from the asteroids, then
+/// either fades it in or blows it up.
+pub(crate) fn hyperspace_system(
+    mut commands: Commands,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Hyperspace,
+            &mut Transform,
+            &mut GfxBounds,
+            &mut Sprite,
+        ),
+        (With<Player>, With<ShadowController>, Without<ShadowOf>),
+    >,
+    mut shadow_query: Query<(Entity, &ShadowOf, &mut Sprite), Without<ShadowController>>,
+    mut flame_query: Query<
+        &mut Sprite,
+        (With<Flame>, Without<ShadowController>, Without<ShadowOf>),
+    >,
+    asteroids_query: Query<&Transform, (With<Asteroid>, Without<Player>)>,
+    mut player_dead_event: EventWriter<PlayerDeadEvent>,
+    win_bounds: Res<GfxBounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (player, mut hyperspace, mut transform, mut bounds, mut sprite) in player_query.iter_mut() {
+        hyperspace.timer.tick(time.delta());
+        let mut landed = false;
+        let alpha = if !hyperspace.timer.finished() {
+            hyperspace.alpha()
+        } else if hyperspace.arrived {
+            landed = true;
+            1.
+        } else {
+            let asteroids: Vec<Vec2> = asteroids_query
+                .iter()
+                .map(|transform| transform.translation.truncate())
+                .collect();
+            let position = random_2d_position_away_from(
+                &mut *rng,
+                &asteroids,
+                settings.asteroid.spawn_player_distance,
+                &win_bounds,
+            );
+            // the shadows follow the new position of the player by themselves
+            transform.translation = position.extend(transform.translation.z);
+            bounds.set_center(position);
+
+            if rng.gen_bool(settings.player.hyperspace_failure_chance.into()) {
+                log::info!(?player, "hyperspace failed");
+                landed = true;
+                player_dead_event.send(PlayerDeadEvent);
+                1.
+            } else {
+                hyperspace.arrived = true;
+                hyperspace.timer.reset();
+                0.
+            }
+        };
+
+        sprite.color.set_a(alpha);
+        if landed {
+            commands
+                .entity(player)
+                .remove::<Hyperspace>()
+                .remove::<InHyperspace>();
+        }
+        for (shadow, _, mut shadow_sprite) in shadow_query
+            .iter_mut()
+            .filter(|(_, shadowof, _)| shadowof.controller == player)
+        {
+            shadow_sprite.color.set_a(alpha);
+            if landed {
+                commands.entity(shadow).remove::<InHyperspace>();
+            }
+        }
+        for mut flame_sprite in flame_query.iter_mut() {
+            flame_sprite.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn jumping_player_fades_out_then_in() {
+        let settings = Settings::default();
+        let fade = settings.player.hyperspace_fade;
+        let mut hyperspace = Hyperspace::new(&settings);
+        assert_eq!(hyperspace.alpha(), 1.);
+
+        hyperspace.timer.tick(fade / 2);
+        assert!((hyperspace.alpha() - 0.5).abs() < 1e-3);
+
+        hyperspace.timer.tick(fade);
+        hyperspace.arrived = true;
+        hyperspace.timer.reset();
+        assert_eq!(hyperspace.alpha(), 0.);
+
+        hyperspace.timer.tick(fade + Duration::from_millis(1));
+        assert_eq!(hyperspace.alpha(), 1.);
+    }
+}
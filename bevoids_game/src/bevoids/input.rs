@@ -0,0 +1,240 @@
+use bevy::{
+    input::{
+        gamepad::{GamepadEvent, GamepadEventType},
+        InputSystem,
+    },
+    log,
+    prelude::*,
+    utils::HashMap,
+};
+
+use super::settings::Settings;
+
+/// Label of the system preparing [ActionState] for a new frame.
+pub(crate) const BEGIN_ACTIONS: &str = "begin_actions";
+/// Label shared by all systems feeding [ActionState], such as the keyboard or gamepads.
+pub(crate) const ACTION_SOURCES: &str = "action_sources";
+
+/// Everything the player is able to do, independent of how it is done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum InputAction {
+    TurnLeft,
+    TurnRight,
+    Modifier,
+    Accelerate,
+    Fire,
+    Hyperspace,
+    Shield,
+}
+
+/// The actions active during the current frame.
+///
+/// Cleared at the beginning of every frame, then filled by the action sources running in
+/// [CoreStage::PreUpdate], labeled [ACTION_SOURCES]. Gameplay only ever reads from here.
+#[derive(Debug, Default)]
+pub(crate) struct ActionState {
+    active: HashMap<InputAction, f32>,
+    previous: HashMap<InputAction, f32>,
+}
+
+impl ActionState {
+    /// Activates `action` fully, as with a key press.
+    pub fn press(&mut self, action: InputAction) {
+        self.press_with(action, 1.);
+    }
+
+    /// Activates `action` by an analog amount in `0..=1`, e.g. the deflection of a stick.
+    ///
+    /// When several sources activate the same action, the largest amount wins.
+    pub fn press_with(&mut self, action: InputAction, value: f32) {
+        let value = value.clamp(0., 1.);
+        if value > 0. {
+            let current = self.active.entry(action).or_default();
+            *current = current.max(value);
+        }
+    }
+
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.active.contains_key(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.pressed(action) && !self.previous.contains_key(&action)
+    }
+
+    pub fn just_released(&self, action: InputAction) -> bool {
+        !self.pressed(action) && self.previous.contains_key(&action)
+    }
+
+    /// How much `action` is activated, `0` when not pressed at all.
+    pub fn value(&self, action: InputAction) -> f32 {
+        self.active.get(&action).copied().unwrap_or_default()
+    }
+
+    fn begin_frame(&mut self) {
+        self.previous = std::mem::take(&mut self.active);
+    }
+}
+
+pub(crate) struct InputActionPlugin;
+
+impl Plugin for InputActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                begin_actions_system.label(BEGIN_ACTIONS).after(InputSystem),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                keyboard_actions_system
+                    .label(ACTION_SOURCES)
+                    .after(BEGIN_ACTIONS),
+            )
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                gamepad_actions_system
+                    .label(ACTION_SOURCES)
+                    .after(BEGIN_ACTIONS),
+            )
+            .add_system(log_gamepad_connections_system);
+    }
+}
+
+fn begin_actions_system(mut actions: ResMut<ActionState>) {
+    actions.begin_frame();
+}
+
+fn keyboard_actions_system(
+    kb: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ActionState>,
+) {
+    let keycodes = &settings.keycodes;
+    for (action, keys) in [
+        (InputAction::TurnLeft, &keycodes.turn_left),
+        (InputAction::TurnRight, &keycodes.turn_right),
+        (InputAction::Modifier, &keycodes.modifier),
+        (InputAction::Accelerate, &keycodes.accelerate),
+        (InputAction::Fire, &keycodes.fire),
+        (InputAction::Hyperspace, &keycodes.hyperspace),
+        (InputAction::Shield, &keycodes.shield),
+    ] {
+        if keys.iter().any(|&key| kb.pressed(key)) {
+            actions.press(action);
+        }
+    }
+}
+
+/// Reads every connected gamepad, so pads plugged in while playing are picked up right away.
+fn gamepad_actions_system(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ActionState>,
+) {
+    let bindings = &settings.gamepad;
+    for &gamepad in gamepads.iter() {
+        for (action, button_types) in [
+            (InputAction::TurnLeft, &bindings.turn_left),
+            (InputAction::TurnRight, &bindings.turn_right),
+            (InputAction::Modifier, &bindings.modifier),
+            (InputAction::Accelerate, &bindings.accelerate),
+            (InputAction::Fire, &bindings.fire),
+            (InputAction::Hyperspace, &bindings.hyperspace),
+            (InputAction::Shield, &bindings.shield),
+        ] {
+            if button_types
+                .iter()
+                .any(|&button_type| buttons.pressed(GamepadButton(gamepad, button_type)))
+            {
+                actions.press(action);
+            }
+        }
+
+        let deflection = axes
+            .get(GamepadAxis(gamepad, bindings.turn_axis))
+            .unwrap_or_default();
+        if let Some((action, amount)) = stick_turn(deflection, bindings.dead_zone) {
+            actions.press(action);
+            actions.press_with(InputAction::Modifier, amount);
+        }
+    }
+}
+
+/// Turn direction and amount in `]0;1]` for a deflection outside the dead zone.
+fn stick_turn(deflection: f32, dead_zone: f32) -> Option<(InputAction, f32)> {
+    let amount = (deflection.abs() - dead_zone) / (1. - dead_zone);
+    if amount > 0. {
+        let action = if deflection < 0. {
+            InputAction::TurnLeft
+        } else {
+            InputAction::TurnRight
+        };
+        Some((action, amount.min(1.)))
+    } else {
+        None
+    }
+}
+
+fn log_gamepad_connections_system(mut events: EventReader<GamepadEvent>) {
+    for GamepadEvent(gamepad, event) in events.iter() {
+        match event {
+            GamepadEventType::Connected => log::info!(?gamepad, "gamepad connected"),
+            GamepadEventType::Disconnected => log::info!(?gamepad, "gamepad disconnected"),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn press_and_release_are_detected_across_frames() {
+        let mut actions = ActionState::default();
+
+        actions.press(InputAction::Fire);
+        assert!(actions.just_pressed(InputAction::Fire));
+
+        actions.begin_frame();
+        actions.press(InputAction::Fire);
+        assert!(actions.pressed(InputAction::Fire));
+        assert!(!actions.just_pressed(InputAction::Fire));
+
+        actions.begin_frame();
+        assert!(!actions.pressed(InputAction::Fire));
+        assert!(actions.just_released(InputAction::Fire));
+    }
+
+    #[test]
+    fn strongest_source_wins() {
+        let mut actions = ActionState::default();
+        actions.press_with(InputAction::Modifier, 0.25);
+        actions.press_with(InputAction::Modifier, 0.75);
+        actions.press_with(InputAction::Modifier, 0.5);
+        assert_eq!(actions.value(InputAction::Modifier), 0.75);
+    }
+
+    #[test]
+    fn stick_within_dead_zone_does_not_turn() {
+        assert_eq!(stick_turn(0.1, 0.2), None);
+        assert_eq!(stick_turn(-0.2, 0.2), None);
+    }
+
+    #[test]
+    fn stick_deflection_scales_from_dead_zone() {
+        assert_eq!(stick_turn(-1., 0.2), Some((InputAction::TurnLeft, 1.)));
+        assert_eq!(stick_turn(0.625, 0.25), Some((InputAction::TurnRight, 0.5)));
+    }
+
+    #[test]
+    fn zero_value_does_not_press() {
+        let mut actions = ActionState::default();
+        actions.press_with(InputAction::Accelerate, 0.);
+        assert!(!actions.pressed(InputAction::Accelerate));
+        assert_eq!(actions.value(InputAction::Accelerate), 0.);
+    }
+}
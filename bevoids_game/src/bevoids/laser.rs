@@ -10,26 +10,47 @@ use crate::bounds::GfxBounds;
 
 use super::{
     movement::{ShadowController, Velocity},
+    pickup::PowerUps,
     player::Player,
-    settings::Settings,
+    settings::{PickupKind, Settings},
 };
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct FireLaserEvent;
 
+/// Sent for each laser spawned - a spread shot spawning several for a single [`FireLaserEvent`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LaserSpawnedEvent;
+
 #[derive(Debug, Component)]
 pub(crate) struct Laser;
 
+/// A laser fired by a saucer at the player.
+#[derive(Debug, Component)]
+pub(crate) struct EnemyLaser;
+
 pub(crate) fn laser_fired_system(
     mut commands: Commands,
     mut events: EventReader<FireLaserEvent>,
+    mut spawned_events: EventWriter<LaserSpawnedEvent>,
     mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
     player_query: Query<(&Transform, &Velocity), (With<Player>, With<ShadowController>)>,
     asset_server: Res<AssetServer>,
     bounds: Res<GfxBounds>,
+    power_ups: Res<PowerUps>,
     settings: Res<Settings>,
 ) {
+    // a spread shot fires lasers to either side as well
+    let spread_angles = if power_ups.is_active(PickupKind::SpreadShot) {
+        let angle = settings.pickup.spread_angle.to_radians();
+        vec![-angle, 0., angle]
+    } else {
+        vec![0.]
+    };
+
+    let player = player_query.iter().next();
     for _ in events.iter() {
+        // the player may have died since firing, the shot being lost along with it
         let (
             &Transform {
                 translation: player_position,
@@ -37,7 +58,10 @@ pub(crate) fn laser_fired_system(
                 ..
             },
             &Velocity(player_velocity),
-        ) = player_query.iter().next().expect("missing player");
+        ) = match player {
+            Some(player) => player,
+            None => continue,
+        };
 
         let laser_texture = asset_server.load(SpriteAsset::GfxLaser);
         let size = settings.laser.size.into();
@@ -45,39 +69,43 @@ pub(crate) fn laser_fired_system(
         let position = player_position
             + player_orientation.mul_vec3(Vec3::new(0., settings.player.gun_ypos, -1.));
 
-        let velocity = player_orientation.mul_vec3(Vec3::Y).truncate();
-        let velocity = velocity
-            * (player_velocity.length() * player_velocity.angle_between(velocity).cos()
-                + settings.laser.speed);
+        for angle in &spread_angles {
+            let orientation = Quat::from_rotation_z(*angle).mul_quat(player_orientation);
+            let velocity = orientation.mul_vec3(Vec3::Y).truncate();
+            let velocity = velocity
+                * (player_velocity.length() * player_velocity.angle_between(velocity).cos()
+                    + settings.laser.speed);
 
-        let laser_id = commands
-            .spawn_bundle(SpriteBundle {
-                texture: laser_texture,
-                transform: Transform {
-                    translation: position,
-                    rotation: Quat::from_rotation_z(PI / 2.).mul_quat(player_orientation),
-                    ..Default::default()
-                },
-                sprite: Sprite {
-                    custom_size: Some(size),
-                    ..Default::default()
-                },
-                ..SpriteBundle::default()
-            })
-            .insert(Laser)
-            .insert(Velocity::from(velocity))
-            .insert(GfxBounds::from_pos_and_size(position.truncate(), size))
-            .insert(DelayedFadeDespawn::new(
-                settings.laser.lifetime,
-                settings.laser.fadeout,
-            ))
-            .id();
+            let laser_id = commands
+                .spawn_bundle(SpriteBundle {
+                    texture: laser_texture.clone(),
+                    transform: Transform {
+                        translation: position,
+                        rotation: Quat::from_rotation_z(PI / 2.).mul_quat(orientation),
+                        ..Default::default()
+                    },
+                    sprite: Sprite {
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    ..SpriteBundle::default()
+                })
+                .insert(Laser)
+                .insert(Velocity::from(velocity))
+                .insert(GfxBounds::from_pos_and_size(position.truncate(), size))
+                .insert(DelayedFadeDespawn::new(
+                    settings.laser.lifetime,
+                    settings.laser.fadeout,
+                ))
+                .id();
+            log::trace!(buller=?laser_id, "spawned laser");
+            spawned_events.send(LaserSpawnedEvent);
+        }
 
         sfx_event.send(
             PlaySfx::new(SoundAsset::Laser)
                 .with_panning((position.x + bounds.width() / 2.) / bounds.width())
                 .into(),
         );
-        log::trace!(buller=?laser_id, "spawned laser");
     }
 }
@@ -36,8 +36,10 @@ pub struct ShadowOf {
 #[derive(Debug, Component)]
 pub struct InsideWindow;
 
+#[derive(Debug, Clone, Copy, Deref)]
 pub struct EnterWindowEvent(Entity);
 
+#[derive(Debug, Clone, Copy, Deref)]
 pub struct ExitWindowEvent(Entity);
 
 pub fn spawn_display_shadows(
@@ -94,22 +96,23 @@ pub fn wrapping_linear_movement_system(
 
         *pos += (Vec2::from(*velocity) * time.delta_seconds()).extend(0.);
 
-        // keep inside window bounds
-        if pos.x > window_half_bounds.x {
-            pos.x -= window_half_bounds.x * 2.;
-        } else if pos.x < -window_half_bounds.x {
-            pos.x += window_half_bounds.x * 2.;
-        }
-        if pos.y > window_half_bounds.y {
-            pos.y -= window_half_bounds.y * 2.;
-        } else if pos.y < -window_half_bounds.y {
-            pos.y += window_half_bounds.y * 2.;
-        }
+        // keep inside window bounds - even when far outside, e.g. after the window shrunk
+        pos.x = wrap(pos.x, window_half_bounds.x);
+        pos.y = wrap(pos.y, window_half_bounds.y);
 
         bounds.set_center(pos.truncate());
     }
 }
 
+/// Wraps `value` into the range `[-half_extent;half_extent]`.
+fn wrap(value: f32, half_extent: f32) -> f32 {
+    if value.abs() > half_extent {
+        (value + half_extent).rem_euclid(half_extent * 2.) - half_extent
+    } else {
+        value
+    }
+}
+
 pub fn non_wrapping_linear_movement_system(
     mut query: Query<
         (Entity, &mut Transform, &mut GfxBounds, &Velocity),
@@ -199,3 +202,27 @@ pub fn move_shadow_system(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn values_inside_are_not_wrapped() {
+        assert_eq!(wrap(0., 100.), 0.);
+        assert_eq!(wrap(100., 100.), 100.);
+        assert_eq!(wrap(-100., 100.), -100.);
+    }
+
+    #[test]
+    fn values_just_outside_wrap_to_the_opposite_side() {
+        assert_eq!(wrap(110., 100.), -90.);
+        assert_eq!(wrap(-110., 100.), 90.);
+    }
+
+    #[test]
+    fn values_far_outside_wrap_back_inside() {
+        assert_eq!(wrap(530., 100.), -70.);
+        assert_eq!(wrap(-530., 100.), 70.);
+    }
+}
@@ -0,0 +1,249 @@
+use bevoids_assets::{SoundAsset, SpriteAsset};
+use bevy::{log, prelude::*};
+use bevy_effects::{
+    despawn::DelayedFadeDespawn,
+    sound::{PlaySfx, SfxCmdEvent},
+};
+use derive_more::{Constructor, Deref};
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use std::{f32::consts::PI, time::Duration};
+
+use crate::bounds::GfxBounds;
+
+use super::{
+    hyperspace::InHyperspace,
+    input::{ActionState, InputAction},
+    laser::FireLaserEvent,
+    movement::{ShadowController, Velocity},
+    player::{Lives, Player},
+    rng::GameRng,
+    settings::{PickupKind, Settings},
+    shield::Shield,
+};
+
+/// Drop a pickup where an asteroid was shot
+#[derive(Debug, Clone, Copy, Deref, Constructor)]
+pub(crate) struct SpawnPickupEvent(Vec3);
+
+/// Pickup touched by the player
+#[derive(Debug, Clone, Copy, Deref, Constructor)]
+pub(crate) struct PickupCollectedEvent(Entity);
+
+// Marks an entity as a pickup
+#[derive(Debug, Component)]
+pub(crate) struct Pickup(PickupKind);
+
+/// The timed power-ups picked up, active until their timers run out.
+#[derive(Default)]
+pub(crate) struct PowerUps(Vec<(PickupKind, Timer)>);
+
+impl PowerUps {
+    /// Activates `kind` for `duration`, starting over when already active.
+    pub fn activate(&mut self, kind: PickupKind, duration: Duration) {
+        self.0.retain(|(active, _)| *active != kind);
+        self.0.push((kind, Timer::new(duration, false)));
+    }
+
+    pub fn is_active(&self, kind: PickupKind) -> bool {
+        self.0.iter().any(|(active, _)| *active == kind)
+    }
+
+    /// Factor the points scored are multiplied by.
+    pub fn score_multiplier(&self, settings: &Settings) -> u32 {
+        if self.is_active(PickupKind::ScoreMultiplier) {
+            settings.pickup.score_multiplier
+        } else {
+            1
+        }
+    }
+
+    /// Advances the timers by `delta`, dropping the power-ups that have run out.
+    pub fn tick(&mut self, delta: Duration) {
+        for (_, timer) in &mut self.0 {
+            timer.tick(delta);
+        }
+        self.0.retain(|(_, timer)| !timer.finished());
+    }
+
+    /// The active power-ups, along with the time left.
+    pub fn iter(&self) -> impl Iterator<Item = (PickupKind, Duration)> + '_ {
+        self.0
+            .iter()
+            .map(|(kind, timer)| (*kind, timer.duration() - timer.elapsed()))
+    }
+}
+
+/// Tint telling the kinds of pickups apart.
+fn tint(kind: PickupKind) -> Color {
+    match kind {
+        PickupKind::RapidFire => Color::ORANGE,
+        PickupKind::SpreadShot => Color::PURPLE,
+        PickupKind::ShieldCharge => Color::CYAN,
+        PickupKind::ExtraLife => Color::GREEN,
+        PickupKind::ScoreMultiplier => Color::GOLD,
+    }
+}
+
+pub(crate) fn reset_power_ups_system(mut commands: Commands) {
+    commands.insert_resource(PowerUps::default());
+}
+
+pub(crate) fn spawn_pickup_event_system(
+    mut spawn_pickup_events: EventReader<SpawnPickupEvent>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
+) {
+    for SpawnPickupEvent(position) in spawn_pickup_events.iter() {
+        let kind = match settings
+            .pickup
+            .drops
+            .choose_weighted(&mut *rng, |drop| drop.weight)
+        {
+            Ok(drop) => drop.kind,
+            Err(_) => continue,
+        };
+
+        let position = position.truncate().extend(settings.pickup.zpos);
+        let velocity = Quat::from_rotation_z(rng.gen_range(0.0..(2. * PI)))
+            .mul_vec3(Vec3::Y)
+            .truncate()
+            * settings.pickup.speed;
+        let size = settings.pickup.size.into();
+
+        let pickup = commands
+            .spawn_bundle(SpriteBundle {
+                texture: asset_server.load(SpriteAsset::GfxPickup),
+                transform: Transform {
+                    translation: position,
+                    ..Transform::default()
+                },
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: tint(kind),
+                    ..Default::default()
+                },
+                ..SpriteBundle::default()
+            })
+            .insert(Pickup(kind))
+            .insert(Velocity::from(velocity))
+            .insert(GfxBounds::from_pos_and_size(position.truncate(), size))
+            .insert(DelayedFadeDespawn::new(
+                settings.pickup.lifetime,
+                settings.pickup.fadeout,
+            ))
+            .id();
+        log::debug!(?pickup, ?kind, "pickup dropped");
+    }
+}
+
+pub(crate) fn collect_pickup_system(
+    mut commands: Commands,
+    mut collected_events: EventReader<PickupCollectedEvent>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+    pickup_query: Query<&Pickup>,
+    mut power_ups: ResMut<PowerUps>,
+    mut shield: ResMut<Shield>,
+    mut lives: ResMut<Lives>,
+    settings: Res<Settings>,
+) {
+    for (pickup, Pickup(kind)) in collected_events
+        .iter()
+        .map(|PickupCollectedEvent(e)| *e)
+        .unique()
+        .filter_map(|e| pickup_query.get(e).ok().map(|pickup| (e, pickup)))
+    {
+        log::info!(?pickup, ?kind, "pickup collected");
+        match kind {
+            PickupKind::RapidFire => {
+                power_ups.activate(*kind, settings.pickup.rapid_fire_duration);
+            }
+            PickupKind::SpreadShot => {
+                power_ups.activate(*kind, settings.pickup.spread_shot_duration);
+            }
+            PickupKind::ScoreMultiplier => {
+                power_ups.activate(*kind, settings.pickup.score_multiplier_duration);
+            }
+            PickupKind::ShieldCharge => shield.charge(&settings),
+            PickupKind::ExtraLife => lives.0 += 1,
+        }
+        sfx_event.send(PlaySfx::new(SoundAsset::Notification).into());
+        commands.entity(pickup).despawn_recursive();
+    }
+}
+
+pub(crate) fn power_ups_system(mut power_ups: ResMut<PowerUps>, time: Res<Time>) {
+    power_ups.tick(time.delta());
+}
+
+/// Keeps firing while fire is held down with rapid fire, the first laser being fired by the
+/// player controls.
+pub(crate) fn rapid_fire_system(
+    mut fire_laser_event: EventWriter<FireLaserEvent>,
+    player_query: Query<Entity, (With<Player>, With<ShadowController>, Without<InHyperspace>)>,
+    power_ups: Res<PowerUps>,
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+) {
+    if !(power_ups.is_active(PickupKind::RapidFire)
+        && actions.pressed(InputAction::Fire)
+        && player_query.iter().next().is_some())
+    {
+        *timer = None;
+        return;
+    }
+
+    let timer = timer.get_or_insert_with(|| Timer::new(settings.pickup.rapid_fire_delay, true));
+    if actions.just_pressed(InputAction::Fire) {
+        timer.reset();
+    } else if timer.tick(time.delta()).just_finished() {
+        log::trace!("rapid fire!");
+        fire_laser_event.send(FireLaserEvent);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn power_ups_run_out() {
+        let mut power_ups = PowerUps::default();
+        power_ups.activate(PickupKind::RapidFire, Duration::from_secs(2));
+        power_ups.activate(PickupKind::SpreadShot, Duration::from_secs(4));
+        power_ups.tick(Duration::from_secs(1));
+
+        // picking up an active power-up starts it over
+        power_ups.activate(PickupKind::RapidFire, Duration::from_secs(2));
+        assert_eq!(
+            power_ups.iter().collect::<Vec<_>>(),
+            vec![
+                (PickupKind::SpreadShot, Duration::from_secs(3)),
+                (PickupKind::RapidFire, Duration::from_secs(2))
+            ]
+        );
+
+        power_ups.tick(Duration::from_secs(2));
+        assert!(!power_ups.is_active(PickupKind::RapidFire));
+        assert!(power_ups.is_active(PickupKind::SpreadShot));
+    }
+
+    #[test]
+    fn score_is_multiplied_while_active() {
+        let settings = Settings::default();
+        let mut power_ups = PowerUps::default();
+        assert_eq!(power_ups.score_multiplier(&settings), 1);
+        power_ups.activate(
+            PickupKind::ScoreMultiplier,
+            settings.pickup.score_multiplier_duration,
+        );
+        assert_eq!(
+            power_ups.score_multiplier(&settings),
+            settings.pickup.score_multiplier
+        );
+    }
+}
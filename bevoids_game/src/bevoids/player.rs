@@ -6,18 +6,20 @@ use bevy_effects::{
     sound::{LoopSfx, PlaySfx, SetPanSfx, SfxCmdEvent, StopSfx},
 };
 use rand::Rng;
-use std::f32::consts::PI;
+use std::{f32::consts::PI, time::Duration};
 
 use crate::{
-    bevoids::{
-        highscore::{HighScoreRepository, Score},
-    },
+    bevoids::highscore::{BoardKey, GameMode, HighScoreBoards, Score},
     bounds::GfxBounds,
 };
 
 use super::{
+    asteroids::Asteroid,
+    hyperspace::InHyperspace,
+    input::{ActionState, InputAction},
     laser::FireLaserEvent,
-    movement::{spawn_display_shadows, InsideWindow, ShadowController, Velocity},
+    movement::{spawn_display_shadows, InsideWindow, ShadowController, ShadowOf, Velocity},
+    rng::GameRng,
     settings::Settings,
     GameState,
 };
@@ -31,53 +33,108 @@ pub(crate) struct Player;
 #[derive(Debug, Component)]
 pub(crate) struct Flame;
 
+/// Number of random positions tried when looking for a safe spot to respawn.
+const RESPAWN_ATTEMPTS: usize = 100;
+
+/// Times per second an invulnerable player blinks.
+const BLINK_RATE: f32 = 5.;
+
+/// Ships left in the current run, including the one playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Lives(pub u32);
+
+/// Time left before a new ship appears, after losing one.
+pub(crate) struct Respawn(Timer);
+
+/// Keeps a player from colliding, blinking until the timer runs out.
+#[derive(Debug, Component)]
+pub(crate) struct Invulnerable(Timer);
+
+impl Invulnerable {
+    #[must_use]
+    pub fn new(duration: Duration) -> Self {
+        Self(Timer::new(duration, false))
+    }
+
+    /// Whether the player is shown, alternating while invulnerable.
+    pub fn is_visible(&self) -> bool {
+        self.0.finished() || (self.0.elapsed_secs() * BLINK_RATE * 2.) as u32 % 2 == 0
+    }
+}
+
 pub(crate) fn player_dead_system(
     mut events: EventReader<PlayerDeadEvent>,
     mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
     player_query: Query<(Entity, &Transform, &GfxBounds), (With<Player>, With<ShadowController>)>,
+    shadow_query: Query<(Entity, &ShadowOf), With<Player>>,
     mut commands: Commands,
     mut state: ResMut<State<GameState>>,
+    mut lives: ResMut<Lives>,
     win_bounds: Res<GfxBounds>,
     settings: Res<Settings>,
     score: Res<Score>,
-    highscore_repository: Res<HighScoreRepository>,
+    highscore_boards: Res<HighScoreBoards>,
     texture_atlas_map: Res<TextureAtlasMap>,
 ) {
-    for _ in events.iter() {
-        for (player, transform, bounds) in player_query.iter() {
-            let panning = (transform.translation.x + win_bounds.width() / 2.) / win_bounds.width();
-            sfx_event.send(
-                PlaySfx::new(SoundAsset::ShipExplode)
-                    .with_panning(panning)
-                    .into(),
-            );
+    // the player and its shadows colliding in the same frame only cost a single life
+    if events.iter().count() == 0 {
+        return;
+    }
 
-            let explosion_atlas = texture_atlas_map.get(SpriteAsset::GfxExplosion).unwrap();
-
-            // TODO: we need to stop the anim!
-            commands.spawn_sprite_animation(
-                explosion_atlas,
-                SpriteAnimation {
-                    fps: settings.general.animation_fps,
-                    position: transform.translation,
-                    size: Some(bounds.size()),
-                    ..Default::default()
-                },
-            );
+    let mut died = false;
+    for (player, transform, bounds) in player_query.iter() {
+        let panning = (transform.translation.x + win_bounds.width() / 2.) / win_bounds.width();
+        sfx_event.send(
+            PlaySfx::new(SoundAsset::ShipExplode)
+                .with_panning(panning)
+                .into(),
+        );
 
-            log::warn!(?player, "player dead");
-            commands
-                .entity(player)
-                .remove::<Player>()
-                .remove::<Velocity>()
-                .insert(Despawn);
-        }
+        let explosion_atlas = texture_atlas_map.get(SpriteAsset::GfxExplosion).unwrap();
 
-        if highscore_repository.position(&score).is_some() {
-            state.set(GameState::NewHighScore).unwrap();
-        } else {
-            state.set(GameState::GameOver).unwrap();
+        // TODO: we need to stop the anim!
+        commands.spawn_sprite_animation(
+            explosion_atlas,
+            SpriteAnimation {
+                fps: settings.general.animation_fps,
+                position: transform.translation,
+                size: Some(bounds.size()),
+                ..Default::default()
+            },
+        );
+
+        log::warn!(?player, "player dead");
+        commands
+            .entity(player)
+            .remove::<Player>()
+            .remove::<Velocity>()
+            .insert(Despawn);
+        // the shadows outlive the player by a frame, and must not collide meanwhile
+        for (shadow, _) in shadow_query
+            .iter()
+            .filter(|(_, shadowof)| shadowof.controller == player)
+        {
+            commands.entity(shadow).remove::<Player>();
         }
+        died = true;
+    }
+    if !died {
+        return;
+    }
+
+    lives.0 = lives.0.saturating_sub(1);
+    if lives.0 > 0 {
+        log::info!(lives = lives.0, "respawning player");
+        sfx_event.send(StopSfx::new(SoundAsset::Thruster).into());
+        commands.insert_resource(Respawn(Timer::new(settings.player.respawn_delay, false)));
+        return;
+    }
+
+    let board = BoardKey::new(GameMode::Classic, &settings.general.difficulty);
+    if highscore_boards.position(&board, &score).is_some() {
+        state.set(GameState::NewHighScore).unwrap();
+    } else {
+        state.set(GameState::GameOver).unwrap();
     }
 }
 
@@ -86,15 +143,104 @@ pub(crate) fn spawn_player_system(
     asset_server: Res<AssetServer>,
     win_bounds: Res<GfxBounds>,
     settings: Res<Settings>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::thread_rng();
-
     let player_position = Vec3::new(
         rng.gen_range(-win_bounds.width() / 2.0..win_bounds.width() / 2.0),
         rng.gen_range(-win_bounds.height() / 2.0..win_bounds.height() / 2.0),
         settings.player.zpos,
     );
 
+    spawn_player(
+        player_position,
+        None,
+        &mut commands,
+        &asset_server,
+        &win_bounds,
+        &settings,
+        &mut *rng,
+    );
+}
+
+/// Spawns a new ship once the respawn delay has passed, away from the asteroids and
+/// invulnerable for a while.
+pub(crate) fn respawn_player_system(
+    mut commands: Commands,
+    mut respawn: Option<ResMut<Respawn>>,
+    asteroids_query: Query<&Transform, With<Asteroid>>,
+    asset_server: Res<AssetServer>,
+    win_bounds: Res<GfxBounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    let respawned = match respawn.as_mut() {
+        Some(respawn) => respawn.0.tick(time.delta()).finished(),
+        None => false,
+    };
+    if !respawned {
+        return;
+    }
+    commands.remove_resource::<Respawn>();
+
+    let asteroids: Vec<Vec2> = asteroids_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let player_position = random_2d_position_away_from(
+        &mut *rng,
+        &asteroids,
+        settings.asteroid.spawn_player_distance,
+        &win_bounds,
+    )
+    .extend(settings.player.zpos);
+
+    spawn_player(
+        player_position,
+        Some(settings.player.invulnerability),
+        &mut commands,
+        &asset_server,
+        &win_bounds,
+        &settings,
+        &mut *rng,
+    );
+}
+
+/// A random position no closer than `distance` to any of the `obstacles` - or the one farthest
+/// from them found, when the window is too crowded.
+pub(crate) fn random_2d_position_away_from(
+    rng: &mut impl Rng,
+    obstacles: &[Vec2],
+    distance: f32,
+    window_bounds: &GfxBounds,
+) -> Vec2 {
+    let (w, h) = (window_bounds.width() / 2.0, window_bounds.height() / 2.0);
+    let mut farthest = (Vec2::ZERO, f32::MIN);
+    for _ in 0..RESPAWN_ATTEMPTS {
+        let position = Vec2::new(rng.gen_range(-w..w), rng.gen_range(-h..h));
+        let clearance = obstacles
+            .iter()
+            .map(|obstacle| obstacle.distance(position))
+            .fold(f32::INFINITY, f32::min);
+        if clearance > distance {
+            return position;
+        }
+        if clearance > farthest.1 {
+            farthest = (position, clearance);
+        }
+    }
+    farthest.0
+}
+
+fn spawn_player(
+    player_position: Vec3,
+    invulnerability: Option<Duration>,
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    win_bounds: &GfxBounds,
+    settings: &Settings,
+    rng: &mut impl Rng,
+) {
     let spaceship_texture = asset_server.load(SpriteAsset::GfxSpaceship);
     let random_rotation = Quat::from_rotation_z(rng.gen_range(0.0..(2. * PI)));
     let player_velocity = random_rotation.mul_vec3(Vec3::Y).truncate() * 1.;
@@ -123,6 +269,11 @@ pub(crate) fn spawn_player_system(
         .insert(ShadowController)
         .insert(InsideWindow)
         .id();
+    if let Some(duration) = invulnerability {
+        commands
+            .entity(player_id)
+            .insert(Invulnerable::new(duration));
+    }
 
     spawn_display_shadows(
         player_id,
@@ -130,39 +281,61 @@ pub(crate) fn spawn_player_system(
         spaceship_texture,
         &Some(|mut cmds: EntityCommands| {
             cmds.insert(Player);
+            if let Some(duration) = invulnerability {
+                cmds.insert(Invulnerable::new(duration));
+            }
         }),
-        &win_bounds,
-        &mut commands,
+        win_bounds,
+        commands,
     );
 
     log::info!(player=?player_id, "player spawned");
 }
 
+/// Blinks the invulnerable players, until they are able to collide again.
+pub(crate) fn invulnerability_system(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (player, mut invulnerable, mut visibility) in player_query.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        visibility.is_visible = invulnerable.is_visible();
+        if invulnerable.0.finished() {
+            commands.entity(player).remove::<Invulnerable>();
+        }
+    }
+}
+
 pub(crate) fn stop_thruster_sound_system(mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>) {
     sfx_event.send(StopSfx::new(SoundAsset::Thruster).into());
 }
 
 pub(crate) fn player_controls_system(
     commands: Commands,
-    kb: Res<Input<KeyCode>>,
+    actions: Res<ActionState>,
     sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
     fire_laser_event: EventWriter<FireLaserEvent>,
-    mut player_query: Query<(Entity, &mut Velocity, &mut Transform), With<Player>>,
+    mut player_query: Query<
+        (Entity, &mut Velocity, &mut Transform),
+        (With<Player>, Without<InHyperspace>),
+    >,
     asset_server: Res<AssetServer>,
     flame_query: Query<Entity, With<Flame>>,
     time: Res<Time>,
     settings: Res<Settings>,
     bounds: Res<GfxBounds>,
 ) {
-    let (player, mut player_velocity, mut player_transform) = player_query
-        .iter_mut()
-        .next()
-        .expect("no player to control");
-
-    fire_laser(&kb, fire_laser_event, &settings);
-    turn_player(&kb, &time, &mut player_transform, &settings);
-    accelleration(
-        &kb,
+    // no player to control while waiting to respawn, or jumping through hyperspace
+    let (player, mut player_velocity, mut player_transform) = match player_query.iter_mut().next() {
+        Some(player) => player,
+        None => return,
+    };
+
+    fire_laser(&actions, fire_laser_event);
+    turn_player(&actions, &time, &mut player_transform, &settings);
+    acceleration(
+        &actions,
         player,
         &mut player_velocity,
         &asset_server,
@@ -176,8 +349,8 @@ pub(crate) fn player_controls_system(
     );
 }
 
-fn accelleration(
-    kb: &Input<KeyCode>,
+fn acceleration(
+    actions: &ActionState,
     player: Entity,
     player_velocity: &mut Velocity,
     asset_server: &AssetServer,
@@ -189,13 +362,11 @@ fn accelleration(
     bounds: &GfxBounds,
     settings: &Settings,
 ) {
-    let keycodes = &settings.keycodes;
-
-    if keycodes.accellerate.iter().any(|&c| kb.pressed(c)) {
-        // accelleration
+    if actions.pressed(InputAction::Accelerate) {
+        // acceleration
         let delta_v = player_transform
             .rotation
-            .mul_vec3(vec3(0., settings.player.accelleration, 0.))
+            .mul_vec3(vec3(0., settings.player.acceleration, 0.))
             .truncate()
             * time.delta_seconds();
         let velocity =
@@ -203,8 +374,9 @@ fn accelleration(
         **player_velocity = velocity.into();
 
         let panning = (player_transform.translation.x + bounds.width() / 2.) / bounds.width();
-        if keycodes.accellerate.iter().any(|&c| kb.just_pressed(c)) {
-            log::trace!("accellerate on");
+        // the flame may be gone while still accelerating, e.g. after a hyperspace jump
+        if flame_query.iter().next().is_none() {
+            log::trace!("accelerate on");
             sfx_event.send(
                 LoopSfx::new(SoundAsset::Thruster)
                     .with_panning(panning)
@@ -216,15 +388,15 @@ fn accelleration(
             sfx_event.send(SetPanSfx::new(SoundAsset::Thruster, panning).into());
         }
     } else {
-        // decellerate
+        // decelerate
         let delta_v = Vec2::from(*player_velocity).normalize()
-            * settings.player.decelleration
+            * settings.player.deceleration
             * time.delta_seconds();
         let velocity =
             (Vec2::from(*player_velocity) - delta_v).clamp_length(0., settings.player.max_speed);
         *player_velocity = velocity.into();
-        if keycodes.accellerate.iter().any(|&c| kb.just_released(c)) {
-            log::trace!("accellerate off");
+        if actions.just_released(InputAction::Accelerate) {
+            log::trace!("accelerate off");
             sfx_event.send(StopSfx::new(SoundAsset::Thruster).into());
             for flame in flame_query.iter() {
                 commands.entity(flame).despawn();
@@ -233,38 +405,29 @@ fn accelleration(
     }
 }
 
-fn fire_laser(
-    kb: &Input<KeyCode>,
-    mut fire_laser_events: EventWriter<FireLaserEvent>,
-    settings: &Settings,
-) {
-    let keycodes = &settings.keycodes;
-
-    if keycodes.fire.iter().any(|c| kb.just_pressed(*c)) {
+fn fire_laser(actions: &ActionState, mut fire_laser_events: EventWriter<FireLaserEvent>) {
+    if actions.just_pressed(InputAction::Fire) {
         log::trace!("fire!");
         fire_laser_events.send(FireLaserEvent);
     }
 }
 
 fn turn_player(
-    kb: &Input<KeyCode>,
+    actions: &ActionState,
     time: &Time,
     player_transform: &mut Transform,
     settings: &Settings,
 ) {
-    let keycodes = &settings.keycodes;
-
-    let speed = if keycodes.modifier.iter().any(|&c| kb.pressed(c)) {
-        settings.player.turn_speed_fast
-    } else {
-        settings.player.turn_speed_slow
-    };
+    // a partially applied modifier, e.g. from an analog stick, turns at an intermediate speed
+    let speed = settings.player.turn_speed_slow
+        + (settings.player.turn_speed_fast - settings.player.turn_speed_slow)
+            * actions.value(InputAction::Modifier);
 
-    if keycodes.turn_left.iter().any(|&c| kb.pressed(c)) {
+    if actions.pressed(InputAction::TurnLeft) {
         player_transform.rotation = player_transform
             .rotation
             .mul_quat(Quat::from_rotation_z(speed * time.delta_seconds()));
-    } else if keycodes.turn_right.iter().any(|&c| kb.pressed(c)) {
+    } else if actions.pressed(InputAction::TurnRight) {
         player_transform.rotation = player_transform
             .rotation
             .mul_quat(Quat::from_rotation_z(-speed * time.delta_seconds()));
@@ -297,3 +460,44 @@ fn spawn_flame(
         .id();
     flame
 }
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn window() -> GfxBounds {
+        GfxBounds::from_pos_and_size(Vec2::ZERO, Vec2::new(800., 800.))
+    }
+
+    #[test]
+    fn respawn_position_keeps_away_from_obstacles() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let obstacles = [Vec2::new(-200., 0.), Vec2::new(200., 0.)];
+        for _ in 0..100 {
+            let position = random_2d_position_away_from(&mut rng, &obstacles, 150., &window());
+            assert!(obstacles
+                .iter()
+                .all(|obstacle| obstacle.distance(position) > 150.));
+        }
+    }
+
+    #[test]
+    fn respawn_position_is_found_in_a_crowded_window() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let position = random_2d_position_away_from(&mut rng, &[Vec2::ZERO], 10000., &window());
+        assert!(position.x.abs() <= 400. && position.y.abs() <= 400.);
+    }
+
+    #[test]
+    fn invulnerable_player_blinks_until_the_timer_runs_out() {
+        let mut invulnerable = Invulnerable::new(Duration::from_secs(1));
+        assert!(invulnerable.is_visible());
+        let blink = Duration::from_secs_f32(0.5 / BLINK_RATE);
+        invulnerable.0.tick(blink.mul_f32(1.5));
+        assert!(!invulnerable.is_visible());
+        invulnerable.0.tick(Duration::from_secs(1));
+        assert!(invulnerable.is_visible());
+    }
+}
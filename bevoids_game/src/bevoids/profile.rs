@@ -0,0 +1,319 @@
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::bevoids::{
+    dirs::project_dirs,
+    files::{move_to_backup, write_atomically},
+    highscore::Score,
+    settings::{BaseSettings, KeyCodes, Settings},
+};
+
+const PROFILES_FILE: &str = "profiles.json";
+
+/// The difficulty or key bindings have been changed from the menus, to be remembered with the
+/// active profile.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ProfileSettingsChangedEvent;
+
+/// A player, remembering their name, preferred difficulty, key bindings and personal bests.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Profile {
+    pub name: String,
+    pub difficulty: String,
+    pub keycodes: KeyCodes,
+    /// Best score on each difficulty, by name of the difficulty.
+    #[serde(default)]
+    pub personal_bests: BTreeMap<String, Score>,
+    /// Names of the achievements unlocked.
+    #[serde(default)]
+    pub achievements: BTreeSet<String>,
+}
+
+impl Profile {
+    /// A profile named `name`, preferring the difficulty and key bindings of the `settings`.
+    #[must_use]
+    pub fn new<T: Into<String>>(name: T, settings: &Settings) -> Self {
+        Self {
+            name: name.into(),
+            difficulty: settings.general.difficulty.clone(),
+            keycodes: settings.keycodes.clone(),
+            personal_bests: BTreeMap::new(),
+            achievements: BTreeSet::new(),
+        }
+    }
+
+    /// Best score on `difficulty`, if any game has been played on it.
+    pub fn personal_best(&self, difficulty: &str) -> Option<Score> {
+        self.personal_bests.get(difficulty).copied()
+    }
+
+    /// Keeps `score` as the personal best on `difficulty`, returning whether it beat the old one.
+    pub fn record(&mut self, difficulty: &str, score: Score) -> bool {
+        match self.personal_bests.get(difficulty) {
+            Some(best) if *best >= score => false,
+            _ => {
+                self.personal_bests.insert(difficulty.to_string(), score);
+                true
+            }
+        }
+    }
+
+    pub fn has_unlocked(&self, achievement: &str) -> bool {
+        self.achievements.contains(achievement)
+    }
+
+    /// Unlocks `achievement`, returning whether it was locked until now.
+    pub fn unlock(&mut self, achievement: &str) -> bool {
+        self.achievements.insert(achievement.to_string())
+    }
+
+    /// Makes the preferences of the profile those of the `settings`.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        if settings
+            .difficulties
+            .iter()
+            .any(|difficulty| difficulty.name == self.difficulty)
+        {
+            settings.general.difficulty = self.difficulty.clone();
+        }
+        settings.keycodes = self.keycodes.clone();
+    }
+}
+
+/// The player profiles, kept in the users data directory - or only in memory, without one.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct Profiles {
+    profiles: Vec<Profile>,
+    /// Name of the profile playing.
+    active: Option<String>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl Profiles {
+    /// Reads the profiles kept in the users data directory, starting out without any if they
+    /// cannot be read.
+    #[must_use]
+    pub fn load() -> Self {
+        let file = project_dirs().map(|dirs| dirs.data_dir().join(PROFILES_FILE));
+        let mut profiles = file.as_deref().map(Self::read).unwrap_or_default();
+        profiles.file = file;
+        profiles
+    }
+
+    /// Reads the profiles kept in `path`, moving a corrupt file aside to a backup rather than
+    /// having the next save overwrite it.
+    fn read(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                match move_to_backup(path) {
+                    Ok(backup) => {
+                        log::warn!(?path, ?backup, "moved corrupt profiles aside: {}", err)
+                    }
+                    Err(backup_err) => {
+                        log::warn!(?path, "unable to back up corrupt profiles: {}", backup_err)
+                    }
+                }
+                Self::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                log::warn!(?path, "unable to read profiles: {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.iter()
+    }
+
+    pub fn active(&self) -> Option<&Profile> {
+        let active = self.active.as_ref()?;
+        self.profiles.iter().find(|profile| &profile.name == active)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Profile> {
+        let active = self.active.as_ref()?;
+        self.profiles
+            .iter_mut()
+            .find(|profile| &profile.name == active)
+    }
+
+    /// Makes the profile named `name` the active one, returning it - if there is such a profile.
+    pub fn select(&mut self, name: &str) -> Option<&Profile> {
+        if self.profiles.iter().any(|profile| profile.name == name) {
+            self.active = Some(name.to_string());
+        }
+        self.active()
+    }
+
+    /// Adds `profile` and makes it the active one, returning false if its name is taken.
+    pub fn create(&mut self, profile: Profile) -> bool {
+        if self.profiles.iter().any(|p| p.name == profile.name) {
+            return false;
+        }
+        self.active = Some(profile.name.clone());
+        self.profiles.push(profile);
+        true
+    }
+
+    /// Removes the profile named `name`, playing without a profile if it was the active one.
+    pub fn remove(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.file {
+            let content = serde_json::to_string_pretty(self).expect("profiles are serializable");
+            let result = match path.parent() {
+                Some(dir) => fs::create_dir_all(dir).and_then(|_| write_atomically(path, content)),
+                None => write_atomically(path, content),
+            };
+            if let Err(err) = result {
+                log::warn!(?path, "unable to save profiles: {}", err);
+            }
+        }
+    }
+}
+
+pub(crate) fn apply_active_profile_system(
+    profiles: Res<Profiles>,
+    mut settings: ResMut<BaseSettings>,
+) {
+    if let Some(profile) = profiles.active() {
+        log::info!(profile = ?profile.name, "playing as");
+        profile.apply_to(&mut settings);
+    }
+}
+
+/// Remembers the difficulty and key bindings picked in the menus with the active profile - but
+/// not those coming from reloading the settings files.
+pub(crate) fn remember_profile_settings_system(
+    mut changed_events: EventReader<ProfileSettingsChangedEvent>,
+    settings: Res<BaseSettings>,
+    mut profiles: ResMut<Profiles>,
+) {
+    if changed_events.iter().last().is_none() {
+        return;
+    }
+    let changed = match profiles.active() {
+        Some(profile) => {
+            profile.difficulty != settings.general.difficulty
+                || profile.keycodes != settings.keycodes
+        }
+        None => false,
+    };
+    if changed {
+        if let Some(profile) = profiles.active_mut() {
+            profile.difficulty = settings.general.difficulty.clone();
+            profile.keycodes = settings.keycodes.clone();
+        }
+        profiles.save();
+    }
+}
+
+pub(crate) fn record_personal_best_system(
+    score: Res<Score>,
+    settings: Res<Settings>,
+    mut profiles: ResMut<Profiles>,
+) {
+    let difficulty = &settings.general.difficulty;
+    let improved = match profiles.active_mut() {
+        Some(profile) => profile.record(difficulty, *score),
+        None => false,
+    };
+    if improved {
+        log::info!(score = u32::from(*score), "new personal best");
+        profiles.save();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::files::TestDir;
+
+    #[test]
+    fn personal_best_is_kept_per_difficulty() {
+        let mut profile = Profile::new("abc", &Settings::default());
+        assert!(profile.record("Normal", Score::new(10)));
+        assert!(!profile.record("Normal", Score::new(5)));
+        assert!(profile.record("Hard", Score::new(5)));
+
+        assert_eq!(profile.personal_best("Normal"), Some(Score::new(10)));
+        assert_eq!(profile.personal_best("Hard"), Some(Score::new(5)));
+        assert_eq!(profile.personal_best("Easy"), None);
+    }
+
+    #[test]
+    fn corrupt_profiles_are_moved_aside() {
+        let dir = TestDir::new();
+        let path = dir.join(PROFILES_FILE);
+        fs::write(&path, "{ not json").unwrap();
+
+        let mut profiles = Profiles::read(&path);
+        assert_eq!(profiles.iter().count(), 0);
+        assert!(!path.exists());
+
+        // saving anew leaves the backup be
+        profiles.file = Some(path.clone());
+        profiles.create(Profile::new("abc", &Settings::default()));
+        profiles.save();
+        assert_eq!(Profiles::read(&path).iter().count(), 1);
+        let backups = dir.backups();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{ not json");
+    }
+
+    #[test]
+    fn profiles_are_selected_by_name() {
+        let settings = Settings::default();
+        let mut profiles = Profiles::default();
+        assert!(profiles.create(Profile::new("abc", &settings)));
+        assert!(profiles.create(Profile::new("def", &settings)));
+        assert!(!profiles.create(Profile::new("abc", &settings)));
+        assert_eq!(profiles.active().unwrap().name, "def");
+
+        assert_eq!(profiles.select("abc").unwrap().name, "abc");
+        assert_eq!(profiles.select("ghi").unwrap().name, "abc");
+
+        profiles.remove("abc");
+        assert!(profiles.active().is_none());
+        assert_eq!(profiles.iter().count(), 1);
+    }
+
+    #[test]
+    fn profiles_saved_before_hyperspace_and_shield_get_the_default_keys() {
+        let mut profile = serde_json::to_value(Profile::new("abc", &Settings::default())).unwrap();
+        let keycodes = profile["keycodes"].as_object_mut().unwrap();
+        keycodes.remove("hyperspace");
+        keycodes.remove("shield");
+        let profile: Profile = serde_json::from_value(profile).unwrap();
+        assert_eq!(profile.keycodes.hyperspace, KeyCodes::default().hyperspace);
+        assert_eq!(profile.keycodes.shield, KeyCodes::default().shield);
+    }
+
+    #[test]
+    fn profile_preferences_apply_to_the_settings() {
+        let mut settings = Settings::default();
+        let mut profile = Profile::new("abc", &settings);
+        profile.difficulty = "Hard".to_string();
+        profile.keycodes.fire = vec![KeyCode::LControl];
+        profile.apply_to(&mut settings);
+        assert_eq!(settings.general.difficulty, "Hard");
+        assert_eq!(settings.keycodes.fire, [KeyCode::LControl]);
+
+        profile.difficulty = "Unknown".to_string();
+        profile.apply_to(&mut settings);
+        assert_eq!(settings.general.difficulty, "Hard");
+    }
+}
@@ -0,0 +1,49 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+/// Source of all randomness in the game.
+///
+/// Given the same seed, the same asteroids are spawned at the same positions.
+pub(crate) struct GameRng(StdRng);
+
+impl GameRng {
+    #[must_use]
+    pub fn new(seed: Option<u64>) -> Self {
+        Self(match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        })
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_numbers() {
+        let (mut a, mut b) = (GameRng::new(Some(42)), GameRng::new(Some(42)));
+        for _ in 0..10 {
+            assert_eq!(a.gen_range(0..1000), b.gen_range(0..1000));
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use bevoids_assets::{SoundAsset, SpriteAsset};
+use bevy::{log, prelude::*};
+use bevy_effects::{
+    animation::{SpawnSpriteAnimation, SpriteAnimation, TextureAtlasMap},
+    despawn::{DelayedFadeDespawn, Despawn},
+    sound::{PlaySfx, SfxCmdEvent},
+};
+use derive_more::{Constructor, Deref};
+use itertools::Itertools;
+use rand::Rng;
+use std::f32::consts::PI;
+
+use crate::{
+    bevoids::highscore::{AddScoreEvent, Score},
+    bounds::GfxBounds,
+};
+
+use super::{
+    laser::EnemyLaser,
+    movement::{
+        EnterWindowEvent, ExitWindowEvent, InsideWindow, NonWrapping, ShadowController, Velocity,
+    },
+    player::Player,
+    rng::GameRng,
+    settings::{SaucerKind, Settings},
+};
+
+/// Angle by which a saucer veers up or down from its horizontal course.
+const COURSE_ANGLE: f32 = PI / 4.;
+
+/// Tint telling the lasers of the saucers from those of the player.
+const ENEMY_LASER_TINT: Color = Color::rgb(1., 0.3, 0.3);
+
+/// Saucer shot by the player - points
+#[derive(Debug, Clone, Copy, Deref, Constructor)]
+pub(crate) struct SaucerShotEvent(Entity);
+
+/// Remove a saucer - no points
+#[derive(Debug, Clone, Copy, Deref, Constructor)]
+pub(crate) struct SaucerExplosionEvent(Entity);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SaucerSize {
+    Large,
+    Small,
+}
+
+impl SaucerSize {
+    pub fn kind(self, settings: &Settings) -> &SaucerKind {
+        match self {
+            SaucerSize::Large => &settings.saucer.large,
+            SaucerSize::Small => &settings.saucer.small,
+        }
+    }
+}
+
+// Marks an entity as a saucer
+#[derive(Debug, Component)]
+pub(crate) struct Saucer {
+    size: SaucerSize,
+    /// Horizontal direction of the flight, 1 for left to right and -1 for right to left.
+    heading: f32,
+    course_timer: Timer,
+    fire_timer: Timer,
+}
+
+/// Time left before the next saucer shows up.
+pub(crate) struct SaucerSpawner(Timer);
+
+pub(crate) fn reset_saucer_spawner_system(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(SaucerSpawner(Timer::new(settings.saucer.spawndelay, false)));
+}
+
+/// Sends a saucer in from the side of the window, once none has been around for a while.
+pub(crate) fn saucer_spawner_system(
+    mut commands: Commands,
+    mut spawner: ResMut<SaucerSpawner>,
+    saucer_query: Query<Entity, With<Saucer>>,
+    asset_server: Res<AssetServer>,
+    win_bounds: Res<GfxBounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    if saucer_query.iter().next().is_some() || !spawner.0.tick(time.delta()).finished() {
+        return;
+    }
+    spawner.0.reset();
+
+    let size = if rng.gen_bool(settings.saucer.small_chance.into()) {
+        SaucerSize::Small
+    } else {
+        SaucerSize::Large
+    };
+    let kind = size.kind(&settings);
+    let heading = if rng.gen_bool(0.5) { 1. } else { -1. };
+
+    // just outside the window, entering it right away
+    let custom_size = Vec2::from(kind.size);
+    let half_height = (win_bounds.height() - custom_size.y) / 2.;
+    let position = Vec3::new(
+        -heading * (win_bounds.width() + custom_size.x) / 2.,
+        rng.gen_range(-half_height..half_height),
+        settings.saucer.zpos,
+    );
+
+    let saucer = commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load(SpriteAsset::GfxSaucer),
+            transform: Transform {
+                translation: position,
+                ..Transform::default()
+            },
+            sprite: Sprite {
+                custom_size: Some(custom_size),
+                ..Default::default()
+            },
+            ..SpriteBundle::default()
+        })
+        .insert(Saucer {
+            size,
+            heading,
+            course_timer: Timer::new(settings.saucer.course_change, true),
+            fire_timer: Timer::new(settings.saucer.fire_delay, true),
+        })
+        .insert(NonWrapping)
+        .insert(Velocity::from(Vec2::new(heading * kind.speed, 0.)))
+        .insert(GfxBounds::from_pos_and_size(
+            position.truncate(),
+            custom_size,
+        ))
+        .id();
+    log::info!(?saucer, ?size, "saucer spawned");
+}
+
+/// Lets saucers collide once inside the window, and removes them once they have left it.
+pub(crate) fn saucer_window_system(
+    mut commands: Commands,
+    mut enter_events: EventReader<EnterWindowEvent>,
+    mut exit_events: EventReader<ExitWindowEvent>,
+    saucer_query: Query<Entity, With<Saucer>>,
+) {
+    for saucer in enter_events
+        .iter()
+        .filter_map(|event| saucer_query.get(**event).ok())
+    {
+        commands.entity(saucer).insert(InsideWindow);
+    }
+    for saucer in exit_events
+        .iter()
+        .filter_map(|event| saucer_query.get(**event).ok())
+    {
+        log::debug!(?saucer, "saucer left");
+        commands.entity(saucer).despawn_recursive();
+    }
+}
+
+/// Makes the saucers veer up and down at random, while keeping their horizontal direction.
+pub(crate) fn saucer_flight_system(
+    mut saucer_query: Query<(&mut Saucer, &mut Velocity)>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (mut saucer, mut velocity) in saucer_query.iter_mut() {
+        if saucer.course_timer.tick(time.delta()).just_finished() {
+            let angle = [-COURSE_ANGLE, 0., COURSE_ANGLE][rng.gen_range(0..3)];
+            let speed = saucer.size.kind(&settings).speed;
+            *velocity =
+                Velocity::from(Vec2::new(saucer.heading * angle.cos(), angle.sin()) * speed);
+        }
+    }
+}
+
+pub(crate) fn saucer_fire_system(
+    mut commands: Commands,
+    mut saucer_query: Query<(&mut Saucer, &Transform), With<InsideWindow>>,
+    player_query: Query<&Transform, (With<Player>, With<ShadowController>)>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+    asset_server: Res<AssetServer>,
+    win_bounds: Res<GfxBounds>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: ResMut<GameRng>,
+) {
+    let player_position = match player_query.iter().next() {
+        Some(transform) => transform.translation.truncate(),
+        None => return,
+    };
+
+    for (mut saucer, saucer_tf) in saucer_query.iter_mut() {
+        if !saucer.fire_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        let inaccuracy = saucer.size.kind(&settings).inaccuracy.to_radians();
+        let direction = aim(
+            saucer_tf.translation.truncate(),
+            player_position,
+            rng.gen_range(-inaccuracy..=inaccuracy),
+        );
+        let position = saucer_tf.translation - Vec3::Z;
+        let size = settings.laser.size.into();
+
+        let laser = commands
+            .spawn_bundle(SpriteBundle {
+                texture: asset_server.load(SpriteAsset::GfxLaser),
+                transform: Transform {
+                    translation: position,
+                    rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+                    ..Default::default()
+                },
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: ENEMY_LASER_TINT,
+                    ..Default::default()
+                },
+                ..SpriteBundle::default()
+            })
+            .insert(EnemyLaser)
+            .insert(Velocity::from(direction * settings.saucer.laser_speed))
+            .insert(GfxBounds::from_pos_and_size(position.truncate(), size))
+            .insert(DelayedFadeDespawn::new(
+                settings.saucer.laser_lifetime,
+                settings.laser.fadeout,
+            ))
+            .id();
+
+        sfx_event.send(
+            PlaySfx::new(SoundAsset::Laser)
+                .with_panning((position.x + win_bounds.width() / 2.) / win_bounds.width())
+                .into(),
+        );
+        log::trace!(?laser, "saucer fired");
+    }
+}
+
+/// Direction from `from` towards `at`, missing by `error` radians.
+fn aim(from: Vec2, at: Vec2, error: f32) -> Vec2 {
+    let direction = (at - from).normalize_or_zero();
+    Quat::from_rotation_z(error)
+        .mul_vec3(direction.extend(0.))
+        .truncate()
+}
+
+pub(crate) fn saucer_shot_system(
+    mut shot_events: EventReader<SaucerShotEvent>,
+    mut remove_event: EventWriter<SaucerExplosionEvent>,
+    mut score_event: EventWriter<AddScoreEvent>,
+    saucer_query: Query<&Saucer>,
+    settings: Res<Settings>,
+) {
+    for (saucer, Saucer { size, .. }) in shot_events
+        .iter()
+        .map(|SaucerShotEvent(e)| *e)
+        .unique()
+        .filter_map(|e| saucer_query.get(e).ok().map(|saucer| (e, saucer)))
+    {
+        log::info!(?saucer, "saucer shot");
+        score_event.send(AddScoreEvent(Score::new(size.kind(&settings).score)));
+        remove_event.send(SaucerExplosionEvent(saucer));
+    }
+}
+
+pub(crate) fn saucer_explosion_system(
+    mut remove_events: EventReader<SaucerExplosionEvent>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+    mut commands: Commands,
+    texture_atlas_map: Res<TextureAtlasMap>,
+    transform_and_bounds_query: Query<(&Transform, &GfxBounds), With<Saucer>>,
+    settings: Res<Settings>,
+    win_bounds: Res<GfxBounds>,
+) {
+    let explosion_atlas = texture_atlas_map.get(SpriteAsset::GfxExplosion).unwrap();
+
+    for (saucer, saucer_tf, saucer_bounds) in remove_events
+        .iter()
+        .map(|SaucerExplosionEvent(e)| *e)
+        .unique()
+        .filter_map(|e| match transform_and_bounds_query.get(e) {
+            Ok((t, b)) => Some((e, t, b)),
+            Err(_) => None,
+        })
+    {
+        log::debug!(?saucer, "saucer exploding");
+
+        let mut anim_position = saucer_tf.translation;
+        anim_position.z -= 1.;
+        commands.spawn_sprite_animation(
+            explosion_atlas,
+            SpriteAnimation {
+                fps: settings.general.animation_fps,
+                position: anim_position,
+                size: Some(Vec2::splat(saucer_bounds.size().max_element())),
+                ..Default::default()
+            },
+        );
+
+        sfx_event.send(
+            PlaySfx::new(SoundAsset::ShipExplode)
+                .with_panning(
+                    (saucer_tf.translation.x + win_bounds.width() / 2.) / win_bounds.width(),
+                )
+                .into(),
+        );
+
+        commands
+            .entity(saucer)
+            .remove_bundle::<(Saucer, Velocity)>()
+            .insert(Despawn);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saucers_aim_at_the_player_missing_by_the_error() {
+        let from = Vec2::new(-10., 5.);
+        let at = Vec2::new(10., 5.);
+        assert!(aim(from, at, 0.).abs_diff_eq(Vec2::X, 1e-6));
+        assert!(aim(from, at, PI / 2.).abs_diff_eq(Vec2::Y, 1e-6));
+        assert!(aim(from, at, -PI).abs_diff_eq(-Vec2::X, 1e-6));
+    }
+}
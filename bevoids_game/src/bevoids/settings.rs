@@ -1,8 +1,24 @@
-use bevy::{math::Vec2, prelude::KeyCode};
+use bevy::{
+    input::gamepad::{GamepadAxisType, GamepadButtonType},
+    math::Vec2,
+    prelude::KeyCode,
+};
+use derive_more::Display;
 use serde_derive::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationSecondsWithFrac};
 use std::time::Duration;
 
+mod difficulty;
+mod loader;
+mod migrate;
+mod validate;
+mod watch;
+
+pub use difficulty::{BaseSettings, Difficulty};
+pub use loader::{update_user_settings, SettingsLoader};
+pub use validate::ValidationError;
+pub use watch::SettingsWatchPlugin;
+
 #[derive(Serialize, Deserialize, Copy, Clone, PartialEq, PartialOrd)]
 pub struct Size {
     pub width: f32,
@@ -11,13 +27,19 @@ pub struct Size {
 
 #[derive(Serialize, Deserialize)]
 pub struct Settings {
+    pub version: u32,
     pub general: General,
     pub laser: Laser,
     pub player: Player,
     pub volume: Volume,
     pub window: Window,
     pub asteroid: Asteroid,
+    pub saucer: Saucer,
+    pub pickup: Pickup,
     pub keycodes: KeyCodes,
+    pub gamepad: GamepadBindings,
+    pub difficulties: Vec<Difficulty>,
+    pub achievements: Vec<Achievement>,
 }
 
 #[serde_as]
@@ -28,15 +50,43 @@ pub struct General {
     pub background_fade: Duration,
     pub asteroids_in_start_menu: usize,
     pub highscores_capacity: u8,
+    /// Name of the selected entry in `difficulties`.
+    pub difficulty: String,
+    /// Base url of a shared leaderboard, used instead of only the local highscores.
+    pub leaderboard_url: Option<String>,
+    /// What to do with highscores that fail to verify against the signature of their board.
+    pub unverified_highscores: UnverifiedHighScores,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct KeyCodes {
     pub turn_left: Vec<KeyCode>,
     pub turn_right: Vec<KeyCode>,
     pub modifier: Vec<KeyCode>,
-    pub accellerate: Vec<KeyCode>,
+    pub accelerate: Vec<KeyCode>,
     pub fire: Vec<KeyCode>,
+    /// Missing from the key bindings of profiles saved before hyperspace was added.
+    #[serde(default = "default_hyperspace_keys")]
+    pub hyperspace: Vec<KeyCode>,
+    /// Missing from the key bindings of profiles saved before the shield was added.
+    #[serde(default = "default_shield_keys")]
+    pub shield: Vec<KeyCode>,
+}
+
+/// Gamepad bindings, used by any connected gamepad.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GamepadBindings {
+    /// Axis turning the player, deflecting it further turns faster.
+    pub turn_axis: GamepadAxisType,
+    /// Deflection of `turn_axis` ignored, in the range `[0;1[`.
+    pub dead_zone: f32,
+    pub turn_left: Vec<GamepadButtonType>,
+    pub turn_right: Vec<GamepadButtonType>,
+    pub modifier: Vec<GamepadButtonType>,
+    pub accelerate: Vec<GamepadButtonType>,
+    pub fire: Vec<GamepadButtonType>,
+    pub hyperspace: Vec<GamepadButtonType>,
+    pub shield: Vec<GamepadButtonType>,
 }
 
 #[serde_as]
@@ -61,24 +111,172 @@ pub struct Asteroid {
     pub split_size_factor: f32,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Flying saucers, showing up from the side of the window now and then to hunt the player.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Saucer {
+    /// Time between saucers, counted while no saucer is around.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub spawndelay: Duration,
+    /// Chance, in the range `[0;1]`, of a saucer being a small one.
+    pub small_chance: f32,
+    /// Time between changes of course.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub course_change: Duration,
+    /// Time between shots at the player.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub fire_delay: Duration,
+    /// Speed of the enemy lasers, otherwise sized and faded out like those of the player.
+    pub laser_speed: f32,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub laser_lifetime: Duration,
+    pub zpos: f32,
+    pub large: SaucerKind,
+    pub small: SaucerKind,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaucerKind {
+    pub size: Size,
+    pub speed: f32,
+    pub score: u32,
+    /// Largest angle, in degrees, by which a shot may miss the player.
+    pub inaccuracy: f32,
+}
+
+/// Pickups dropped by shot asteroids, powering up the player picking them up.
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Pickup {
+    /// Chance, in the range `[0;1]`, of a shot asteroid dropping a pickup.
+    pub drop_chance: f32,
+    /// Kinds of pickups dropped, each picked with a chance relative to its weight.
+    pub drops: Vec<PickupDrop>,
+    pub size: Size,
+    pub speed: f32,
+    pub zpos: f32,
+    /// How long a pickup drifts around before fading out.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub lifetime: Duration,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub fadeout: Duration,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub rapid_fire_duration: Duration,
+    /// Time between lasers fired while holding down fire with rapid fire.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub rapid_fire_delay: Duration,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub spread_shot_duration: Duration,
+    /// Angle, in degrees, between the lasers of a spread shot.
+    pub spread_angle: f32,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub score_multiplier_duration: Duration,
+    pub score_multiplier: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PickupDrop {
+    pub kind: PickupKind,
+    pub weight: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Display, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    /// Keeps firing while fire is held down, for a while.
+    #[display(fmt = "Rapid fire")]
+    RapidFire,
+    /// Fires three lasers at a time, for a while.
+    #[display(fmt = "Spread shot")]
+    SpreadShot,
+    /// Recharges the shield fully.
+    #[display(fmt = "Shield charge")]
+    ShieldCharge,
+    #[display(fmt = "Extra life")]
+    ExtraLife,
+    /// Multiplies the points scored, for a while.
+    #[display(fmt = "Score multiplier")]
+    ScoreMultiplier,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Window {
+    /// Size of the window, when not in fullscreen.
     pub width: u32,
     pub height: u32,
+    pub mode: WindowMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowMode {
+    Windowed,
+    Fullscreen,
+}
+
+/// An achievement, unlocked by a player once its condition is met during a run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Achievement {
+    pub name: String,
+    pub description: String,
+    pub condition: Condition,
 }
 
+/// What a single run must accomplish to unlock an [Achievement].
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Reach a score.
+    Score(u32),
+    /// Shoot a number of asteroids.
+    AsteroidsShot(usize),
+    /// Stay alive for a while.
+    Survive(#[serde_as(as = "DurationSecondsWithFrac<f64>")] Duration),
+    /// Shoot every asteroid on the field without ever thrusting.
+    ClearFieldWithoutThrust,
+}
+
+/// Handling of highscores whose signature does not verify, e.g. after editing the file by hand.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnverifiedHighScores {
+    /// Keep them, but mark them in the highscore menu.
+    Flag,
+    /// Remove them from the boards.
+    Drop,
+}
+
+#[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct Player {
     pub size: Size,
     pub zpos: f32,
     pub gun_ypos: f32,
-    pub accelleration: f32,
-    pub decelleration: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
     pub turn_speed_slow: f32,
     pub turn_speed_fast: f32,
     pub max_speed: f32,
     pub flame_size: Size,
     pub flame_ypos: f32,
+    /// Number of ships in a game, losing one on each collision.
+    pub lives: u32,
+    /// Delay before a new ship appears, after losing one.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub respawn_delay: Duration,
+    /// How long a new ship is kept from colliding, blinking meanwhile.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub invulnerability: Duration,
+    /// Time between hyperspace jumps.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub hyperspace_cooldown: Duration,
+    /// How long the ship takes to fade out, and again to fade in, when jumping.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub hyperspace_fade: Duration,
+    /// Chance, in the range `[0;1]`, of the ship blowing up as it arrives from a jump.
+    pub hyperspace_failure_chance: f32,
+    /// How long the shield stays up, starting out with full energy.
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub shield_capacity: Duration,
+    /// Seconds of shield regained per second the shield is down.
+    pub shield_recharge_rate: f32,
 }
 
 #[serde_as]
@@ -92,12 +290,32 @@ pub struct Laser {
     pub fadeout: Duration,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Volume {
     pub laser: f32,
     pub thruster: f32,
     pub ship_explosion: f32,
     pub asteroid_explosion: f32,
+    pub shield: f32,
+}
+
+impl WindowMode {
+    #[must_use]
+    pub fn toggled(self) -> Self {
+        match self {
+            WindowMode::Windowed => WindowMode::Fullscreen,
+            WindowMode::Fullscreen => WindowMode::Windowed,
+        }
+    }
+}
+
+impl From<WindowMode> for bevy::window::WindowMode {
+    fn from(mode: WindowMode) -> Self {
+        match mode {
+            WindowMode::Windowed => bevy::window::WindowMode::Windowed,
+            WindowMode::Fullscreen => bevy::window::WindowMode::BorderlessFullscreen,
+        }
+    }
 }
 
 impl From<Size> for Vec2 {
@@ -106,14 +324,100 @@ impl From<Size> for Vec2 {
     }
 }
 
+/// A key bound to more than one action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyConflict {
+    pub key: KeyCode,
+    pub first: &'static str,
+    pub second: &'static str,
+}
+
+impl KeyCodes {
+    /// The keys bound to each action, along with the name of the action.
+    pub fn bindings(&self) -> [(&'static str, &Vec<KeyCode>); 7] {
+        [
+            ("turn_left", &self.turn_left),
+            ("turn_right", &self.turn_right),
+            ("modifier", &self.modifier),
+            ("accelerate", &self.accelerate),
+            ("fire", &self.fire),
+            ("hyperspace", &self.hyperspace),
+            ("shield", &self.shield),
+        ]
+    }
+
+    pub fn bindings_mut(&mut self) -> [(&'static str, &mut Vec<KeyCode>); 7] {
+        [
+            ("turn_left", &mut self.turn_left),
+            ("turn_right", &mut self.turn_right),
+            ("modifier", &mut self.modifier),
+            ("accelerate", &mut self.accelerate),
+            ("fire", &mut self.fire),
+            ("hyperspace", &mut self.hyperspace),
+            ("shield", &mut self.shield),
+        ]
+    }
+
+    /// Keys bound to more than one action.
+    pub fn conflicts(&self) -> Vec<KeyConflict> {
+        let bindings = self.bindings();
+        let mut conflicts = Vec::new();
+        for (i, (first, keys)) in bindings.iter().enumerate() {
+            for (second, other_keys) in &bindings[i + 1..] {
+                conflicts.extend(
+                    keys.iter()
+                        .filter(|key| other_keys.contains(key))
+                        .map(|&key| KeyConflict { key, first, second }),
+                );
+            }
+        }
+        conflicts
+    }
+}
+
 impl Default for KeyCodes {
     fn default() -> Self {
         Self {
             turn_left: vec![KeyCode::Left, KeyCode::A],
             turn_right: vec![KeyCode::Right, KeyCode::D],
             modifier: vec![KeyCode::RControl, KeyCode::LControl],
-            accellerate: vec![KeyCode::Up, KeyCode::W],
+            accelerate: vec![KeyCode::Up, KeyCode::W],
             fire: vec![KeyCode::Space],
+            hyperspace: default_hyperspace_keys(),
+            shield: default_shield_keys(),
         }
     }
 }
+
+fn default_hyperspace_keys() -> Vec<KeyCode> {
+    vec![KeyCode::Down, KeyCode::S]
+}
+
+fn default_shield_keys() -> Vec<KeyCode> {
+    vec![KeyCode::LShift, KeyCode::RShift]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_keycodes_have_no_conflicts() {
+        assert!(KeyCodes::default().conflicts().is_empty());
+    }
+
+    #[test]
+    fn key_bound_twice_is_a_conflict() {
+        let mut keycodes = KeyCodes::default();
+        keycodes.fire.push(KeyCode::W);
+
+        assert_eq!(
+            keycodes.conflicts(),
+            vec![KeyConflict {
+                key: KeyCode::W,
+                first: "accelerate",
+                second: "fire"
+            }]
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use derive_more::{Deref, DerefMut, Display};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{loader::merge, Settings};
+
+/// A named set of overrides for the `asteroid`, `player`, `laser` and `saucer` settings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    pub name: String,
+    /// Number of highscores kept on the boards of this difficulty, instead of
+    /// `general.highscores_capacity`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highscores_capacity: Option<u8>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub asteroid: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub player: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub laser: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub saucer: Map<String, Value>,
+}
+
+/// [Settings] as loaded from the settings files, before applying the selected difficulty.
+///
+/// Menus change these, while the game itself uses the resulting [Settings] resource.
+#[derive(Deref, DerefMut)]
+pub struct BaseSettings(pub Settings);
+
+#[derive(Debug, Display)]
+pub enum DifficultyError {
+    #[display(fmt = "unknown difficulty `{}`", _0)]
+    Unknown(String),
+    #[display(fmt = "difficulty `{}` overrides unknown setting `{}`", name, key)]
+    UnknownKey { name: String, key: String },
+    #[display(fmt = "difficulty `{}`: {}", name, source)]
+    Schema {
+        name: String,
+        source: serde_json::Error,
+    },
+}
+
+impl std::error::Error for DifficultyError {}
+
+impl Settings {
+    pub fn difficulty(&self, name: &str) -> Option<&Difficulty> {
+        self.difficulties.iter().find(|d| d.name == name)
+    }
+
+    /// These settings, with the overrides of the selected difficulty applied.
+    pub fn effective(&self) -> Result<Settings, DifficultyError> {
+        self.with_difficulty(&self.general.difficulty)
+    }
+
+    /// These settings, with the overrides of the difficulty `name` applied.
+    pub fn with_difficulty(&self, name: &str) -> Result<Settings, DifficultyError> {
+        let difficulty = self
+            .difficulty(name)
+            .ok_or_else(|| DifficultyError::Unknown(name.to_string()))?;
+
+        let mut settings = serde_json::to_value(self).expect("settings are serializable");
+        for (section, overrides) in [
+            ("asteroid", &difficulty.asteroid),
+            ("player", &difficulty.player),
+            ("laser", &difficulty.laser),
+            ("saucer", &difficulty.saucer),
+        ] {
+            if let Some(key) = overrides
+                .keys()
+                .find(|&key| settings[section].get(key).is_none())
+            {
+                return Err(DifficultyError::UnknownKey {
+                    name: name.to_string(),
+                    key: format!("{}.{}", section, key),
+                });
+            }
+            merge(&mut settings[section], Value::Object(overrides.clone()));
+        }
+
+        serde_json::from_value(settings).map_err(|source| DifficultyError::Schema {
+            name: name.to_string(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn difficulty(overrides: Value) -> Difficulty {
+        let mut difficulty = json!({ "name": "Test" });
+        merge(&mut difficulty, overrides);
+        serde_json::from_value(difficulty).unwrap()
+    }
+
+    #[test]
+    fn overrides_are_applied() {
+        let mut settings = Settings::default();
+        settings.difficulties.push(difficulty(json!({
+            "asteroid": { "split_number": 5 },
+            "player": { "max_speed": 1.0 }
+        })));
+
+        let hard = settings.with_difficulty("Test").unwrap();
+        assert_eq!(hard.asteroid.split_number, 5);
+        assert_eq!(hard.player.max_speed, 1.0);
+        assert_eq!(hard.laser.speed, settings.laser.speed);
+    }
+
+    #[test]
+    fn unknown_difficulty_is_an_error() {
+        assert!(matches!(
+            Settings::default().with_difficulty("Impossible"),
+            Err(DifficultyError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn misspelled_override_is_an_error() {
+        let mut settings = Settings::default();
+        settings.difficulties.push(difficulty(json!({
+            "asteroid": { "split_numbr": 5 }
+        })));
+
+        assert!(matches!(
+            settings.with_difficulty("Test"),
+            Err(DifficultyError::UnknownKey { key, .. }) if key == "asteroid.split_numbr"
+        ));
+    }
+}
@@ -0,0 +1,352 @@
+use bevy::log;
+use derive_more::Display;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::bevoids::{dirs::project_dirs, files::write_atomically};
+
+use super::{
+    migrate::{migrate, Migration, SETTINGS_VERSION},
+    Settings, ValidationError,
+};
+
+const EMBEDDED_SETTINGS: &[u8] = include_bytes!("../../settings.json");
+const USER_SETTINGS_FILE: &str = "settings.json";
+
+/// Errors encountered while loading one of the settings layers.
+#[derive(Debug, Display)]
+pub enum SettingsError {
+    #[display(fmt = "unable to read {}: {}", "path.display()", source)]
+    Io { path: PathBuf, source: io::Error },
+    #[display(fmt = "{}: {}", "path.display()", source)]
+    Syntax {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[display(fmt = "{}: `{}`: {}", "path.display()", key, message)]
+    Schema {
+        path: PathBuf,
+        key: String,
+        message: String,
+    },
+    #[display(
+        fmt = "{}: settings version {} is not supported by this version of the game",
+        "path.display()",
+        version
+    )]
+    Version { path: PathBuf, version: u32 },
+    #[display(fmt = "invalid settings: {}", _0)]
+    Invalid(ValidationError),
+}
+
+impl std::error::Error for SettingsError {}
+
+/// Loads [Settings] in layers, each overriding the previous one field by field:
+///
+/// 1. the `settings.json` embedded in the executable.
+/// 2. `settings.json` in the users config directory - if it exists.
+/// 3. an explicitly given settings file.
+#[derive(Debug, Clone)]
+pub struct SettingsLoader {
+    user_file: Option<PathBuf>,
+    explicit_file: Option<PathBuf>,
+}
+
+impl SettingsLoader {
+    #[must_use]
+    pub fn new(explicit_file: Option<PathBuf>) -> Self {
+        Self {
+            user_file: user_settings_path(),
+            explicit_file,
+        }
+    }
+
+    /// Files that (may) contribute to the loaded [Settings], lowest priority first.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.user_file
+            .iter()
+            .chain(self.explicit_file.iter())
+            .map(PathBuf::as_path)
+    }
+
+    pub fn load(&self) -> Result<Settings, SettingsError> {
+        let mut merged = embedded_settings_value();
+
+        if let Some(path) = self.user_file.as_deref() {
+            if path.exists() {
+                merge_layer(&mut merged, path, true)?;
+            }
+        }
+        // the file given on the command line belongs to whoever gave it, and is never rewritten
+        if let Some(path) = self.explicit_file.as_deref() {
+            merge_layer(&mut merged, path, false)?;
+        }
+
+        let settings =
+            Settings::deserialize(&merged).expect("settings layers verified while merging");
+        settings.validate().map_err(SettingsError::Invalid)?;
+        Ok(settings)
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        serde_json::from_slice(EMBEDDED_SETTINGS).expect("unable to parse embedded settings")
+    }
+}
+
+/// Location of the users own settings file.
+pub fn user_settings_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join(USER_SETTINGS_FILE))
+}
+
+/// Merges `patch` into the users own settings file, creating the file if needed.
+///
+/// The file is upgraded first, should it still be written for an older version of the game.
+pub fn update_user_settings(patch: Value) -> Result<(), SettingsError> {
+    let path = user_settings_path().ok_or_else(|| SettingsError::Io {
+        path: PathBuf::from(USER_SETTINGS_FILE),
+        source: io::Error::new(io::ErrorKind::NotFound, "no config directory"),
+    })?;
+    let io_error = |source| SettingsError::Io {
+        path: path.clone(),
+        source,
+    };
+
+    let mut settings = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|source| SettingsError::Syntax {
+            path: path.clone(),
+            source,
+        })?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Value::Object(Default::default()),
+        Err(err) => return Err(io_error(err)),
+    };
+    if let Migration::Unsupported { version } = migrate(&mut settings) {
+        return Err(SettingsError::Version {
+            path: path.clone(),
+            version,
+        });
+    }
+    merge(&mut settings, patch);
+    merge(&mut settings, json!({ "version": SETTINGS_VERSION }));
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    let content = serde_json::to_string_pretty(&settings).expect("settings are serializable");
+    write_atomically(&path, content).map_err(io_error)
+}
+
+fn embedded_settings_value() -> Value {
+    serde_json::from_slice(EMBEDDED_SETTINGS).expect("unable to parse embedded settings")
+}
+
+/// Merges the settings file at `path` on top of `merged`, upgrading it first if written for an
+/// older version of the game - and writing the upgrade back to the file if `write_back`.
+fn merge_layer(merged: &mut Value, path: &Path, write_back: bool) -> Result<(), SettingsError> {
+    let content = fs::read_to_string(path).map_err(|source| SettingsError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut layer: Value =
+        serde_json::from_str(&content).map_err(|source| SettingsError::Syntax {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    match migrate(&mut layer) {
+        Migration::Current => {}
+        Migration::Upgraded { from } if write_back => {
+            if let Err(err) = write_migrated(path, &content, &layer, from) {
+                log::warn!(?path, "unable to write migrated settings: {}", err);
+            }
+        }
+        Migration::Upgraded { from } => {
+            log::info!(?path, "settings of version {} upgraded while loading", from);
+        }
+        Migration::Unsupported { version } => {
+            return Err(SettingsError::Version {
+                path: path.to_path_buf(),
+                version,
+            })
+        }
+    }
+
+    merge_value(merged, layer, path)
+}
+
+/// Replaces a settings file with its migrated content, keeping the original as a backup
+/// next to it, e.g. `settings.json.v0.bak`.
+fn write_migrated(path: &Path, original: &str, migrated: &Value, from: u32) -> io::Result<()> {
+    let mut backup = OsString::from(path.as_os_str());
+    backup.push(format!(".v{}.bak", from));
+    fs::write(&backup, original)?;
+    write_atomically(path, serde_json::to_string_pretty(migrated)?)?;
+    log::info!(?path, ?backup, "settings migrated");
+    Ok(())
+}
+
+/// Merges `layer` on top of `merged`, ensuring the result still describes valid [Settings].
+fn merge_value(merged: &mut Value, layer: Value, path: &Path) -> Result<(), SettingsError> {
+    let mut candidate = merged.clone();
+    merge(&mut candidate, layer.clone());
+    match Settings::deserialize(&candidate) {
+        Ok(_) => {
+            *merged = candidate;
+            Ok(())
+        }
+        Err(err) => Err(SettingsError::Schema {
+            path: path.to_path_buf(),
+            key: offending_key(merged, &layer).unwrap_or_default(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// Recursively merges objects, any other value in `overlay` replaces the one in `base`.
+pub(crate) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Applies the leaves of `layer` one at a time, returning the dotted path of the first
+/// one that makes `base` invalid.
+fn offending_key(base: &Value, layer: &Value) -> Option<String> {
+    let mut leaves = Vec::new();
+    collect_leaves(layer, &mut Vec::new(), &mut leaves);
+
+    let mut probe = base.clone();
+    for (key, value) in leaves {
+        let overlay = key.iter().rev().fold(value, |value, k| {
+            Value::Object([(k.clone(), value)].into_iter().collect())
+        });
+        merge(&mut probe, overlay);
+        if Settings::deserialize(&probe).is_err() {
+            return Some(key.join("."));
+        }
+    }
+    None
+}
+
+fn collect_leaves(value: &Value, key: &mut Vec<String>, leaves: &mut Vec<(Vec<String>, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                key.push(k.clone());
+                collect_leaves(v, key, leaves);
+                key.pop();
+            }
+        }
+        _ => leaves.push((key.clone(), value.clone())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::files::TestDir;
+
+    #[test]
+    fn partial_layer_merges_field_by_field() {
+        let mut merged = embedded_settings_value();
+        merge_value(
+            &mut merged,
+            json!({ "player": { "max_speed": 123.0 } }),
+            Path::new("user.json"),
+        )
+        .unwrap();
+
+        let settings = Settings::deserialize(&merged).unwrap();
+        assert_eq!(settings.player.max_speed, 123.0);
+        assert_eq!(
+            settings.player.turn_speed_fast,
+            Settings::default().player.turn_speed_fast
+        );
+    }
+
+    #[test]
+    fn invalid_value_is_reported_by_key() {
+        let mut merged = embedded_settings_value();
+        let err = merge_value(
+            &mut merged,
+            json!({ "window": { "width": 640 }, "player": { "max_speed": "fast" } }),
+            Path::new("user.json"),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, SettingsError::Schema { key, .. } if key == "player.max_speed"));
+        assert_eq!(
+            merged,
+            embedded_settings_value(),
+            "failed layer must not be merged"
+        );
+    }
+
+    #[test]
+    fn syntax_error_is_reported_with_location() {
+        let dir = TestDir::new();
+        let path = dir.join("settings.json");
+        std::fs::write(&path, "{\n  \"window\": {\n    \"width\": ,\n  }\n}").unwrap();
+
+        let err = merge_layer(&mut embedded_settings_value(), &path, true).unwrap_err();
+        match err {
+            SettingsError::Syntax { source, .. } => assert_eq!(source.line(), 3),
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    fn legacy_layer(dir: &TestDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn backup_of(path: &Path) -> PathBuf {
+        let mut backup = OsString::from(path.as_os_str());
+        backup.push(".v0.bak");
+        PathBuf::from(backup)
+    }
+
+    #[test]
+    fn only_changed_layers_are_written_back() {
+        let dir = TestDir::new();
+        let partial = r#"{ "player": { "max_speed": 123.0 } }"#;
+        let path = legacy_layer(&dir, "partial.json", partial);
+        merge_layer(&mut embedded_settings_value(), &path, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), partial);
+        assert!(!backup_of(&path).exists());
+
+        let legacy = r#"{ "player": { "accelleration": 123.0 } }"#;
+        let path = legacy_layer(&dir, "legacy.json", legacy);
+        merge_layer(&mut embedded_settings_value(), &path, true).unwrap();
+        assert_ne!(std::fs::read_to_string(&path).unwrap(), legacy);
+        assert_eq!(std::fs::read_to_string(backup_of(&path)).unwrap(), legacy);
+    }
+
+    #[test]
+    fn explicit_layer_is_upgraded_without_being_rewritten() {
+        let dir = TestDir::new();
+        let legacy = r#"{ "player": { "accelleration": 123.0 } }"#;
+        let path = legacy_layer(&dir, "explicit.json", legacy);
+        let mut merged = embedded_settings_value();
+        merge_layer(&mut merged, &path, false).unwrap();
+
+        assert_eq!(
+            Settings::deserialize(&merged).unwrap().player.acceleration,
+            123.0
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), legacy);
+        assert!(!backup_of(&path).exists());
+    }
+}
@@ -0,0 +1,128 @@
+use serde_json::{Map, Value};
+
+/// Version of the settings layout understood by this build.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// Upgrades, in order, settings of version `n` to version `n + 1`, returning whether anything
+/// needed changing.
+const MIGRATIONS: [fn(&mut Map<String, Value>) -> bool; SETTINGS_VERSION as usize] =
+    [rename_misspelled_keys];
+
+/// Outcome of migrating a settings layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Migration {
+    Current,
+    Upgraded { from: u32 },
+    Unsupported { version: u32 },
+}
+
+/// Upgrades a (partial) settings layer to [SETTINGS_VERSION].
+///
+/// Layers without a `version` are considered to be version 0. Older layers none of the
+/// migrations change, e.g. partial ones, are left untouched as [Migration::Current].
+pub fn migrate(settings: &mut Value) -> Migration {
+    let settings = match settings.as_object_mut() {
+        Some(settings) => settings,
+        None => return Migration::Current,
+    };
+
+    let version = settings
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as u32;
+    if version == SETTINGS_VERSION {
+        return Migration::Current;
+    } else if version > SETTINGS_VERSION {
+        return Migration::Unsupported { version };
+    }
+
+    let mut changed = false;
+    for migration in &MIGRATIONS[version as usize..] {
+        changed |= migration(settings);
+    }
+    if !changed {
+        return Migration::Current;
+    }
+    settings.insert("version".into(), SETTINGS_VERSION.into());
+    Migration::Upgraded { from: version }
+}
+
+/// Version 0 -> 1: fix the spelling of `accelleration`, `decelleration` and `accellerate`.
+fn rename_misspelled_keys(settings: &mut Map<String, Value>) -> bool {
+    let renamed = [
+        rename_key(settings, "player", "accelleration", "acceleration"),
+        rename_key(settings, "player", "decelleration", "deceleration"),
+        rename_key(settings, "keycodes", "accellerate", "accelerate"),
+    ];
+    renamed.contains(&true)
+}
+
+/// Renames the key `from` in `section` to `to`, returning whether there was such a key.
+fn rename_key(settings: &mut Map<String, Value>, section: &str, from: &str, to: &str) -> bool {
+    if let Some(section) = settings.get_mut(section).and_then(Value::as_object_mut) {
+        if let Some(value) = section.remove(from) {
+            section.entry(to).or_insert(value);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn unversioned_settings_are_upgraded() {
+        let mut settings = json!({
+            "player": { "accelleration": 1.0, "decelleration": 2.0 },
+            "keycodes": { "accellerate": ["Up"] }
+        });
+
+        assert_eq!(migrate(&mut settings), Migration::Upgraded { from: 0 });
+        assert_eq!(
+            settings,
+            json!({
+                "version": SETTINGS_VERSION,
+                "player": { "acceleration": 1.0, "deceleration": 2.0 },
+                "keycodes": { "accelerate": ["Up"] }
+            })
+        );
+    }
+
+    #[test]
+    fn renaming_keeps_values_using_the_new_name() {
+        let mut settings = json!({ "player": { "accelleration": 1.0, "acceleration": 2.0 } });
+        migrate(&mut settings);
+        assert_eq!(settings["player"], json!({ "acceleration": 2.0 }));
+    }
+
+    #[test]
+    fn unversioned_settings_without_legacy_keys_are_untouched() {
+        let mut settings = json!({ "player": { "max_speed": 1.0 } });
+        let expected = settings.clone();
+        assert_eq!(migrate(&mut settings), Migration::Current);
+        assert_eq!(settings, expected);
+    }
+
+    #[test]
+    fn current_settings_are_untouched() {
+        let mut settings = json!({ "version": SETTINGS_VERSION, "player": { "max_speed": 1.0 } });
+        let expected = settings.clone();
+        assert_eq!(migrate(&mut settings), Migration::Current);
+        assert_eq!(settings, expected);
+    }
+
+    #[test]
+    fn newer_settings_are_unsupported() {
+        let mut settings = json!({ "version": SETTINGS_VERSION + 1 });
+        assert_eq!(
+            migrate(&mut settings),
+            Migration::Unsupported {
+                version: SETTINGS_VERSION + 1
+            }
+        );
+    }
+}
@@ -0,0 +1,830 @@
+use derive_more::Display;
+use itertools::Itertools;
+use std::{mem, time::Duration};
+
+use super::{
+    Asteroid, GamepadBindings, General, Laser, Pickup, Player, Saucer, Settings, Size, Volume,
+    Window,
+};
+
+/// A single semantic constraint violated by [Settings].
+#[derive(Debug, Display, Clone, PartialEq)]
+pub enum Violation {
+    #[display(fmt = "general.animation_fps ({}) must be positive", _0)]
+    AnimationFps(f32),
+    #[display(fmt = "general.background_fade must be longer than zero")]
+    BackgroundFade,
+    #[display(fmt = "general.highscores_capacity must be at least 1")]
+    HighscoresCapacity,
+    #[display(fmt = "general.leaderboard_url `{}` must be an http(s) url", _0)]
+    LeaderboardUrl(String),
+    #[display(fmt = "window size ({}x{}) must be positive", width, height)]
+    WindowSize { width: u32, height: u32 },
+    #[display(
+        fmt = "asteroid.size_min ({}) must be positive and less than asteroid.size_max ({})",
+        min,
+        max
+    )]
+    AsteroidSize { min: f32, max: f32 },
+    #[display(
+        fmt = "asteroid.speed_min ({}) must be non-negative and less than asteroid.speed_max ({})",
+        min,
+        max
+    )]
+    AsteroidSpeed { min: f32, max: f32 },
+    #[display(
+        fmt = "asteroid.zpos_min ({}) must be less than asteroid.zpos_max ({})",
+        min,
+        max
+    )]
+    AsteroidZpos { min: f32, max: f32 },
+    #[display(fmt = "asteroid.max_score ({}) must be non-negative", _0)]
+    AsteroidMaxScore(f32),
+    #[display(
+        fmt = "asteroid.spawndelay_min ({:?}) must be longer than zero, and no longer than asteroid.spawndelay_initial ({:?})",
+        min,
+        initial
+    )]
+    AsteroidSpawnDelay { min: Duration, initial: Duration },
+    #[display(
+        fmt = "asteroid.spawndelay_multiplier ({}) must be in the range ]0;1]",
+        _0
+    )]
+    AsteroidSpawnDelayMultiplier(f32),
+    #[display(fmt = "asteroid.split_size_factor ({}) must be in the range ]0;1[", _0)]
+    AsteroidSplitSizeFactor(f32),
+    #[display(
+        fmt = "player.turn_speed_slow ({}) must be positive and no faster than player.turn_speed_fast ({})",
+        slow,
+        fast
+    )]
+    PlayerTurnSpeed { slow: f32, fast: f32 },
+    #[display(fmt = "player.max_speed ({}) must be positive", _0)]
+    PlayerMaxSpeed(f32),
+    #[display(
+        fmt = "player.acceleration ({}) and player.deceleration ({}) must be non-negative",
+        acceleration,
+        deceleration
+    )]
+    PlayerAcceleration {
+        acceleration: f32,
+        deceleration: f32,
+    },
+    #[display(fmt = "player.lives must be at least 1")]
+    PlayerLives,
+    #[display(
+        fmt = "player.hyperspace_failure_chance ({}) must be in the range [0;1]",
+        _0
+    )]
+    HyperspaceFailureChance(f32),
+    #[display(fmt = "player.hyperspace_fade must be longer than zero")]
+    HyperspaceFade,
+    #[display(
+        fmt = "player.shield_capacity must be longer than zero, and player.shield_recharge_rate ({}) non-negative",
+        _0
+    )]
+    Shield(f32),
+    #[display(fmt = "{} must be positive", _0)]
+    NonPositiveSize(&'static str),
+    #[display(fmt = "laser.speed ({}) must be positive", _0)]
+    LaserSpeed(f32),
+    #[display(fmt = "laser.lifetime and laser.fadeout must be longer than zero")]
+    LaserLifetime,
+    #[display(fmt = "saucer.small_chance ({}) must be in the range [0;1]", _0)]
+    SaucerSmallChance(f32),
+    #[display(
+        fmt = "saucer.spawndelay, saucer.course_change, saucer.fire_delay and saucer.laser_lifetime must be longer than zero"
+    )]
+    SaucerDelay,
+    #[display(fmt = "{} ({}) must be positive", name, speed)]
+    SaucerSpeed { name: &'static str, speed: f32 },
+    #[display(fmt = "pickup.drop_chance ({}) must be in the range [0;1]", _0)]
+    PickupDropChance(f32),
+    #[display(fmt = "pickup.drops must have a positive total weight to drop pickups")]
+    PickupDrops,
+    #[display(
+        fmt = "pickup.lifetime, pickup.fadeout and the durations of the pickups must be longer than zero"
+    )]
+    PickupDuration,
+    #[display(fmt = "pickup.score_multiplier must be at least 1")]
+    PickupScoreMultiplier,
+    #[display(fmt = "volume.{} ({}) must be in the range [0;1]", sound, volume)]
+    Volume { sound: &'static str, volume: f32 },
+    #[display(fmt = "gamepad.dead_zone ({}) must be in the range [0;1[", _0)]
+    GamepadDeadZone(f32),
+    #[display(fmt = "general.difficulty `{}` is not one of the difficulties", _0)]
+    UnknownDifficulty(String),
+    #[display(fmt = "difficulty `{}` is defined more than once", _0)]
+    DuplicateDifficulty(String),
+    #[display(fmt = "{}", _0)]
+    DifficultyOverride(String),
+    #[display(fmt = "achievement `{}` is defined more than once", _0)]
+    DuplicateAchievement(String),
+    #[display(fmt = "achievements must have a name")]
+    UnnamedAchievement,
+    #[display(fmt = "difficulty `{}`: {}", name, violation)]
+    Difficulty {
+        name: String,
+        violation: Box<Violation>,
+    },
+}
+
+/// All constraints violated by [Settings].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError(pub Vec<Violation>);
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join("; "))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Settings {
+    /// Checks the semantic constraints the game relies on, e.g. non-empty random ranges.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut violations = Vec::new();
+        self.general.validate(&mut violations);
+        self.window.validate(&mut violations);
+        self.asteroid.validate(&mut violations);
+        self.player.validate(&mut violations);
+        self.laser.validate(&mut violations);
+        self.saucer.validate(&mut violations);
+        self.pickup.validate(&mut violations);
+        self.volume.validate(&mut violations);
+        self.gamepad.validate(&mut violations);
+        self.validate_difficulties(&mut violations);
+        self.validate_achievements(&mut violations);
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError(violations))
+        }
+    }
+
+    fn validate_difficulties(&self, violations: &mut Vec<Violation>) {
+        if self.difficulty(&self.general.difficulty).is_none() {
+            violations.push(Violation::UnknownDifficulty(
+                self.general.difficulty.clone(),
+            ));
+        }
+        for name in self.difficulties.iter().map(|d| &d.name).duplicates() {
+            violations.push(Violation::DuplicateDifficulty(name.clone()));
+        }
+        for difficulty in &self.difficulties {
+            if difficulty.highscores_capacity == Some(0) {
+                violations.push(Violation::Difficulty {
+                    name: difficulty.name.clone(),
+                    violation: Box::new(Violation::HighscoresCapacity),
+                });
+            }
+        }
+
+        // kinds of violations inherited from the base settings are already reported
+        let inherited = self
+            .overridable_violations()
+            .iter()
+            .map(mem::discriminant)
+            .collect::<Vec<_>>();
+        for difficulty in &self.difficulties {
+            let settings = match self.with_difficulty(&difficulty.name) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    violations.push(Violation::DifficultyOverride(err.to_string()));
+                    continue;
+                }
+            };
+
+            violations.extend(
+                settings
+                    .overridable_violations()
+                    .into_iter()
+                    .filter(|violation| !inherited.contains(&mem::discriminant(violation)))
+                    .map(|violation| Violation::Difficulty {
+                        name: difficulty.name.clone(),
+                        violation: Box::new(violation),
+                    }),
+            );
+        }
+    }
+
+    fn validate_achievements(&self, violations: &mut Vec<Violation>) {
+        if self.achievements.iter().any(|a| a.name.trim().is_empty()) {
+            violations.push(Violation::UnnamedAchievement);
+        }
+        for name in self.achievements.iter().map(|a| &a.name).duplicates() {
+            violations.push(Violation::DuplicateAchievement(name.clone()));
+        }
+    }
+
+    /// Violations within the sections a difficulty is able to override.
+    fn overridable_violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.asteroid.validate(&mut violations);
+        self.player.validate(&mut violations);
+        self.laser.validate(&mut violations);
+        self.saucer.validate(&mut violations);
+        violations
+    }
+}
+
+impl General {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(self.animation_fps > 0.) {
+            violations.push(Violation::AnimationFps(self.animation_fps));
+        }
+        if self.background_fade.is_zero() {
+            violations.push(Violation::BackgroundFade);
+        }
+        if self.highscores_capacity == 0 {
+            violations.push(Violation::HighscoresCapacity);
+        }
+        if let Some(url) = &self.leaderboard_url {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                violations.push(Violation::LeaderboardUrl(url.clone()));
+            }
+        }
+    }
+}
+
+impl Window {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if self.width == 0 || self.height == 0 {
+            violations.push(Violation::WindowSize {
+                width: self.width,
+                height: self.height,
+            });
+        }
+    }
+}
+
+impl Asteroid {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(self.size_min > 0. && self.size_min < self.size_max) {
+            violations.push(Violation::AsteroidSize {
+                min: self.size_min,
+                max: self.size_max,
+            });
+        }
+        if !(self.speed_min >= 0. && self.speed_min < self.speed_max) {
+            violations.push(Violation::AsteroidSpeed {
+                min: self.speed_min,
+                max: self.speed_max,
+            });
+        }
+        if !(self.zpos_min < self.zpos_max) {
+            violations.push(Violation::AsteroidZpos {
+                min: self.zpos_min,
+                max: self.zpos_max,
+            });
+        }
+        if !(self.max_score >= 0.) {
+            violations.push(Violation::AsteroidMaxScore(self.max_score));
+        }
+        if self.spawndelay_min.is_zero() || self.spawndelay_min > self.spawndelay_initial {
+            violations.push(Violation::AsteroidSpawnDelay {
+                min: self.spawndelay_min,
+                initial: self.spawndelay_initial,
+            });
+        }
+        if !(self.spawndelay_multiplier > 0. && self.spawndelay_multiplier <= 1.) {
+            violations.push(Violation::AsteroidSpawnDelayMultiplier(
+                self.spawndelay_multiplier,
+            ));
+        }
+        if !(self.split_size_factor > 0. && self.split_size_factor < 1.) {
+            violations.push(Violation::AsteroidSplitSizeFactor(self.split_size_factor));
+        }
+    }
+}
+
+impl Player {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(self.turn_speed_slow > 0. && self.turn_speed_slow <= self.turn_speed_fast) {
+            violations.push(Violation::PlayerTurnSpeed {
+                slow: self.turn_speed_slow,
+                fast: self.turn_speed_fast,
+            });
+        }
+        if !(self.max_speed > 0.) {
+            violations.push(Violation::PlayerMaxSpeed(self.max_speed));
+        }
+        if !(self.acceleration >= 0. && self.deceleration >= 0.) {
+            violations.push(Violation::PlayerAcceleration {
+                acceleration: self.acceleration,
+                deceleration: self.deceleration,
+            });
+        }
+        if self.lives == 0 {
+            violations.push(Violation::PlayerLives);
+        }
+        if !(0. ..=1.).contains(&self.hyperspace_failure_chance) {
+            violations.push(Violation::HyperspaceFailureChance(
+                self.hyperspace_failure_chance,
+            ));
+        }
+        if self.hyperspace_fade.is_zero() {
+            violations.push(Violation::HyperspaceFade);
+        }
+        if self.shield_capacity.is_zero() || !(self.shield_recharge_rate >= 0.) {
+            violations.push(Violation::Shield(self.shield_recharge_rate));
+        }
+        validate_size(&self.size, "player.size", violations);
+        validate_size(&self.flame_size, "player.flame_size", violations);
+    }
+}
+
+impl Laser {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(self.speed > 0.) {
+            violations.push(Violation::LaserSpeed(self.speed));
+        }
+        if self.lifetime.is_zero() || self.fadeout.is_zero() {
+            violations.push(Violation::LaserLifetime);
+        }
+        validate_size(&self.size, "laser.size", violations);
+    }
+}
+
+impl Saucer {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(0. ..=1.).contains(&self.small_chance) {
+            violations.push(Violation::SaucerSmallChance(self.small_chance));
+        }
+        if [
+            self.spawndelay,
+            self.course_change,
+            self.fire_delay,
+            self.laser_lifetime,
+        ]
+        .iter()
+        .any(Duration::is_zero)
+        {
+            violations.push(Violation::SaucerDelay);
+        }
+        for (name, speed) in [
+            ("saucer.laser_speed", self.laser_speed),
+            ("saucer.large.speed", self.large.speed),
+            ("saucer.small.speed", self.small.speed),
+        ] {
+            if !(speed > 0.) {
+                violations.push(Violation::SaucerSpeed { name, speed });
+            }
+        }
+        validate_size(&self.large.size, "saucer.large.size", violations);
+        validate_size(&self.small.size, "saucer.small.size", violations);
+    }
+}
+
+impl Pickup {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(0. ..=1.).contains(&self.drop_chance) {
+            violations.push(Violation::PickupDropChance(self.drop_chance));
+        }
+        if self.drop_chance > 0. && self.drops.iter().map(|drop| drop.weight).sum::<u32>() == 0 {
+            violations.push(Violation::PickupDrops);
+        }
+        if [
+            self.lifetime,
+            self.fadeout,
+            self.rapid_fire_duration,
+            self.rapid_fire_delay,
+            self.spread_shot_duration,
+            self.score_multiplier_duration,
+        ]
+        .iter()
+        .any(Duration::is_zero)
+        {
+            violations.push(Violation::PickupDuration);
+        }
+        if self.score_multiplier == 0 {
+            violations.push(Violation::PickupScoreMultiplier);
+        }
+        validate_size(&self.size, "pickup.size", violations);
+    }
+}
+
+impl Volume {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        for (sound, volume) in [
+            ("laser", self.laser),
+            ("thruster", self.thruster),
+            ("ship_explosion", self.ship_explosion),
+            ("asteroid_explosion", self.asteroid_explosion),
+            ("shield", self.shield),
+        ] {
+            if !(0.0..=1.0).contains(&volume) {
+                violations.push(Violation::Volume { sound, volume });
+            }
+        }
+    }
+}
+
+impl GamepadBindings {
+    fn validate(&self, violations: &mut Vec<Violation>) {
+        if !(0.0..1.0).contains(&self.dead_zone) {
+            violations.push(Violation::GamepadDeadZone(self.dead_zone));
+        }
+    }
+}
+
+fn validate_size(size: &Size, name: &'static str, violations: &mut Vec<Violation>) {
+    if !(size.width > 0. && size.height > 0.) {
+        violations.push(Violation::NonPositiveSize(name));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn violations_of(modify: impl FnOnce(&mut Settings)) -> Vec<Violation> {
+        let mut settings = Settings::default();
+        modify(&mut settings);
+        settings.validate().err().map(|e| e.0).unwrap_or_default()
+    }
+
+    #[test]
+    fn default_settings_are_valid() {
+        assert_eq!(Settings::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn all_violations_are_reported() {
+        let violations = violations_of(|s| {
+            s.asteroid.size_min = 200.;
+            s.laser.speed = 0.;
+            s.volume.laser = 2.;
+        });
+        assert_eq!(violations.len(), 3);
+    }
+
+    #[test]
+    fn animation_fps_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.general.animation_fps = 0.),
+            vec![Violation::AnimationFps(0.)]
+        );
+    }
+
+    #[test]
+    fn background_fade_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.general.background_fade = Duration::ZERO),
+            vec![Violation::BackgroundFade]
+        );
+    }
+
+    #[test]
+    fn highscores_capacity_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.general.highscores_capacity = 0),
+            vec![Violation::HighscoresCapacity]
+        );
+    }
+
+    #[test]
+    fn leaderboard_url_must_be_http() {
+        assert_eq!(
+            violations_of(|s| s.general.leaderboard_url = Some("localhost:8080".into())),
+            vec![Violation::LeaderboardUrl("localhost:8080".into())]
+        );
+        assert_eq!(
+            violations_of(|s| s.general.leaderboard_url = Some("http://localhost:8080".into())),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn window_must_have_a_size() {
+        assert_eq!(
+            violations_of(|s| s.window.height = 0),
+            vec![Violation::WindowSize {
+                width: 800,
+                height: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn asteroid_size_range_must_not_be_empty() {
+        assert_eq!(
+            violations_of(|s| s.asteroid.size_min = s.asteroid.size_max),
+            vec![Violation::AsteroidSize {
+                min: 150.,
+                max: 150.
+            }]
+        );
+        assert!(matches!(
+            violations_of(|s| s.asteroid.size_min = 0.)[..],
+            [Violation::AsteroidSize { .. }]
+        ));
+    }
+
+    #[test]
+    fn asteroid_speed_range_must_not_be_empty() {
+        assert!(matches!(
+            violations_of(|s| s.asteroid.speed_min = s.asteroid.speed_max)[..],
+            [Violation::AsteroidSpeed { .. }]
+        ));
+        assert!(matches!(
+            violations_of(|s| s.asteroid.speed_min = -1.)[..],
+            [Violation::AsteroidSpeed { .. }]
+        ));
+    }
+
+    #[test]
+    fn asteroid_zpos_range_must_not_be_empty() {
+        assert!(matches!(
+            violations_of(|s| s.asteroid.zpos_min = s.asteroid.zpos_max)[..],
+            [Violation::AsteroidZpos { .. }]
+        ));
+    }
+
+    #[test]
+    fn asteroid_max_score_must_not_be_negative() {
+        assert_eq!(
+            violations_of(|s| s.asteroid.max_score = -1.),
+            vec![Violation::AsteroidMaxScore(-1.)]
+        );
+    }
+
+    #[test]
+    fn asteroid_spawndelay_min_must_be_within_initial() {
+        assert!(matches!(
+            violations_of(|s| s.asteroid.spawndelay_min = Duration::ZERO)[..],
+            [Violation::AsteroidSpawnDelay { .. }]
+        ));
+        assert!(matches!(
+            violations_of(|s| s.asteroid.spawndelay_min = s.asteroid.spawndelay_initial * 2)[..],
+            [Violation::AsteroidSpawnDelay { .. }]
+        ));
+    }
+
+    #[test]
+    fn asteroid_spawndelay_multiplier_must_not_increase_delay() {
+        assert_eq!(
+            violations_of(|s| s.asteroid.spawndelay_multiplier = 1.5),
+            vec![Violation::AsteroidSpawnDelayMultiplier(1.5)]
+        );
+        assert_eq!(
+            violations_of(|s| s.asteroid.spawndelay_multiplier = 0.),
+            vec![Violation::AsteroidSpawnDelayMultiplier(0.)]
+        );
+    }
+
+    #[test]
+    fn asteroid_split_size_factor_must_shrink() {
+        assert_eq!(
+            violations_of(|s| s.asteroid.split_size_factor = 1.),
+            vec![Violation::AsteroidSplitSizeFactor(1.)]
+        );
+        assert_eq!(
+            violations_of(|s| s.asteroid.split_size_factor = 0.),
+            vec![Violation::AsteroidSplitSizeFactor(0.)]
+        );
+    }
+
+    #[test]
+    fn player_turn_speeds_must_be_ordered() {
+        assert!(matches!(
+            violations_of(|s| s.player.turn_speed_slow = s.player.turn_speed_fast + 1.)[..],
+            [Violation::PlayerTurnSpeed { .. }]
+        ));
+        assert!(matches!(
+            violations_of(|s| s.player.turn_speed_slow = 0.)[..],
+            [Violation::PlayerTurnSpeed { .. }]
+        ));
+    }
+
+    #[test]
+    fn player_max_speed_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.player.max_speed = 0.),
+            vec![Violation::PlayerMaxSpeed(0.)]
+        );
+    }
+
+    #[test]
+    fn player_acceleration_must_not_be_negative() {
+        assert!(matches!(
+            violations_of(|s| s.player.deceleration = -1.)[..],
+            [Violation::PlayerAcceleration { .. }]
+        ));
+    }
+
+    #[test]
+    fn player_must_have_a_life() {
+        assert_eq!(
+            violations_of(|s| s.player.lives = 0),
+            vec![Violation::PlayerLives]
+        );
+    }
+
+    #[test]
+    fn hyperspace_must_be_able_to_succeed() {
+        assert_eq!(
+            violations_of(|s| s.player.hyperspace_failure_chance = 1.5),
+            vec![Violation::HyperspaceFailureChance(1.5)]
+        );
+        assert_eq!(
+            violations_of(|s| s.player.hyperspace_fade = Duration::ZERO),
+            vec![Violation::HyperspaceFade]
+        );
+    }
+
+    #[test]
+    fn shield_must_have_capacity() {
+        assert_eq!(
+            violations_of(|s| s.player.shield_capacity = Duration::ZERO),
+            vec![Violation::Shield(
+                Settings::default().player.shield_recharge_rate
+            )]
+        );
+        assert_eq!(
+            violations_of(|s| s.player.shield_recharge_rate = -1.),
+            vec![Violation::Shield(-1.)]
+        );
+    }
+
+    #[test]
+    fn sizes_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.player.size.width = 0.),
+            vec![Violation::NonPositiveSize("player.size")]
+        );
+        assert_eq!(
+            violations_of(|s| s.player.flame_size.height = -1.),
+            vec![Violation::NonPositiveSize("player.flame_size")]
+        );
+        assert_eq!(
+            violations_of(|s| s.laser.size.width = 0.),
+            vec![Violation::NonPositiveSize("laser.size")]
+        );
+        assert_eq!(
+            violations_of(|s| s.saucer.small.size.height = 0.),
+            vec![Violation::NonPositiveSize("saucer.small.size")]
+        );
+    }
+
+    #[test]
+    fn laser_speed_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.laser.speed = -1.),
+            vec![Violation::LaserSpeed(-1.)]
+        );
+    }
+
+    #[test]
+    fn laser_lifetime_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.laser.lifetime = Duration::ZERO),
+            vec![Violation::LaserLifetime]
+        );
+        assert_eq!(
+            violations_of(|s| s.laser.fadeout = Duration::ZERO),
+            vec![Violation::LaserLifetime]
+        );
+    }
+
+    #[test]
+    fn saucer_small_chance_must_be_within_range() {
+        assert_eq!(
+            violations_of(|s| s.saucer.small_chance = 1.5),
+            vec![Violation::SaucerSmallChance(1.5)]
+        );
+    }
+
+    #[test]
+    fn saucer_delays_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.saucer.fire_delay = Duration::ZERO),
+            vec![Violation::SaucerDelay]
+        );
+    }
+
+    #[test]
+    fn saucer_speeds_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.saucer.large.speed = 0.),
+            vec![Violation::SaucerSpeed {
+                name: "saucer.large.speed",
+                speed: 0.
+            }]
+        );
+    }
+
+    #[test]
+    fn pickups_must_be_droppable() {
+        assert_eq!(
+            violations_of(|s| s.pickup.drop_chance = -0.1),
+            vec![Violation::PickupDropChance(-0.1)]
+        );
+        assert_eq!(
+            violations_of(|s| s.pickup.drops.clear()),
+            vec![Violation::PickupDrops]
+        );
+        // no drops needed when nothing is dropped
+        let violations = violations_of(|s| {
+            s.pickup.drop_chance = 0.;
+            s.pickup.drops.clear();
+        });
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn pickup_durations_must_be_positive() {
+        assert_eq!(
+            violations_of(|s| s.pickup.rapid_fire_delay = Duration::ZERO),
+            vec![Violation::PickupDuration]
+        );
+        assert_eq!(
+            violations_of(|s| s.pickup.score_multiplier = 0),
+            vec![Violation::PickupScoreMultiplier]
+        );
+    }
+
+    #[test]
+    fn volumes_must_be_within_range() {
+        assert_eq!(
+            violations_of(|s| s.volume.thruster = 1.5),
+            vec![Violation::Volume {
+                sound: "thruster",
+                volume: 1.5
+            }]
+        );
+        assert_eq!(
+            violations_of(|s| s.volume.asteroid_explosion = -0.1),
+            vec![Violation::Volume {
+                sound: "asteroid_explosion",
+                volume: -0.1
+            }]
+        );
+    }
+
+    #[test]
+    fn selected_difficulty_must_exist() {
+        assert_eq!(
+            violations_of(|s| s.general.difficulty = "Impossible".into()),
+            vec![Violation::UnknownDifficulty("Impossible".into())]
+        );
+    }
+
+    #[test]
+    fn difficulty_names_must_be_unique() {
+        assert_eq!(
+            violations_of(|s| s.difficulties.push(s.difficulties[0].clone())),
+            vec![Violation::DuplicateDifficulty(
+                Settings::default().difficulties[0].name.clone()
+            )]
+        );
+    }
+
+    #[test]
+    fn difficulties_must_result_in_valid_settings() {
+        let violations = violations_of(|s| {
+            s.difficulties[0]
+                .asteroid
+                .insert("split_size_factor".into(), 2.0.into());
+        });
+        assert!(matches!(
+            &violations[..],
+            [Violation::Difficulty { violation, .. }]
+                if **violation == Violation::AsteroidSplitSizeFactor(2.)
+        ));
+    }
+
+    #[test]
+    fn difficulty_highscores_capacity_must_be_positive() {
+        let violations = violations_of(|s| s.difficulties[0].highscores_capacity = Some(0));
+        assert!(matches!(
+            violations.as_slice(),
+            [Violation::Difficulty { violation, .. }]
+                if **violation == Violation::HighscoresCapacity
+        ));
+    }
+
+    #[test]
+    fn achievement_names_must_be_unique() {
+        assert_eq!(
+            violations_of(|s| s.achievements.push(s.achievements[0].clone())),
+            vec![Violation::DuplicateAchievement(
+                Settings::default().achievements[0].name.clone()
+            )]
+        );
+        assert_eq!(
+            violations_of(|s| s.achievements[0].name = " ".into()),
+            vec![Violation::UnnamedAchievement]
+        );
+    }
+
+    #[test]
+    fn gamepad_dead_zone_must_be_within_range() {
+        assert_eq!(
+            violations_of(|s| s.gamepad.dead_zone = 1.),
+            vec![Violation::GamepadDeadZone(1.)]
+        );
+    }
+}
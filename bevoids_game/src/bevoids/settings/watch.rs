@@ -0,0 +1,63 @@
+use bevy::{core::FixedTimestep, log, prelude::*};
+use std::{fs, time::SystemTime};
+
+use super::{BaseSettings, SettingsLoader};
+
+/// Polls the files used by the [SettingsLoader] resource, reloading [BaseSettings] when any
+/// of them change. The game picks up the resulting [Settings](super::Settings) from there.
+///
+/// If the new settings can't be loaded, the error is logged and the current settings kept.
+#[derive(Debug, Default)]
+pub struct SettingsWatchPlugin;
+
+impl Plugin for SettingsWatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(initialize_watched_files_system)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(FixedTimestep::step(1.0))
+                    .with_system(reload_changed_settings_system),
+            );
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct WatchedFiles(Vec<Option<SystemTime>>);
+
+impl WatchedFiles {
+    fn read(loader: &SettingsLoader) -> Self {
+        Self(
+            loader
+                .files()
+                .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+                .collect(),
+        )
+    }
+}
+
+fn initialize_watched_files_system(mut commands: Commands, loader: Res<SettingsLoader>) {
+    for path in loader.files() {
+        log::info!(?path, "watching settings");
+    }
+    commands.insert_resource(WatchedFiles::read(&loader));
+}
+
+fn reload_changed_settings_system(
+    loader: Res<SettingsLoader>,
+    mut watched: ResMut<WatchedFiles>,
+    mut settings: ResMut<BaseSettings>,
+) {
+    let current = WatchedFiles::read(&loader);
+    if current == *watched {
+        return;
+    }
+    *watched = current;
+
+    match loader.load() {
+        Ok(loaded) => {
+            log::info!("settings reloaded");
+            **settings = loaded;
+        }
+        Err(err) => log::error!("unable to reload settings, keeping current: {}", err),
+    }
+}
@@ -0,0 +1,149 @@
+use bevoids_assets::SoundAsset;
+use bevy::{log, prelude::*};
+use bevy_effects::sound::{LoopSfx, SfxCmdEvent, StopSfx};
+use std::time::Duration;
+
+use super::{
+    input::{ActionState, InputAction},
+    movement::ShadowController,
+    player::Player,
+    settings::Settings,
+};
+
+/// Tint of the player - and its shadows - while the shield is up.
+const SHIELD_TINT: Color = Color::rgb(0.4, 0.8, 1.);
+
+/// The energy shield of the player, draining while up and recharging while down.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Shield {
+    /// Seconds of shield left.
+    energy: f32,
+    up: bool,
+    /// Set once drained while up, keeping the shield down until asked for anew.
+    drained: bool,
+}
+
+impl Shield {
+    /// A lowered shield with full energy.
+    #[must_use]
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            energy: settings.player.shield_capacity.as_secs_f32(),
+            up: false,
+            drained: false,
+        }
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.up
+    }
+
+    /// Energy left, from 0 when drained to 1 when full.
+    pub fn fraction(&self, settings: &Settings) -> f32 {
+        self.energy / settings.player.shield_capacity.as_secs_f32()
+    }
+
+    /// Recharges the shield fully.
+    pub fn charge(&mut self, settings: &Settings) {
+        self.energy = settings.player.shield_capacity.as_secs_f32();
+    }
+
+    /// Drains or recharges the shield by `delta`, then raises it if asked to and there is energy
+    /// left. Once drained, the shield stays down until released and asked for again. Returns
+    /// whether the shield went up or down.
+    pub fn update(&mut self, raise: bool, delta: Duration, settings: &Settings) -> bool {
+        let was_up = self.up;
+        self.energy = if self.up {
+            (self.energy - delta.as_secs_f32()).max(0.)
+        } else {
+            (self.energy + settings.player.shield_recharge_rate * delta.as_secs_f32())
+                .min(settings.player.shield_capacity.as_secs_f32())
+        };
+        if !raise {
+            self.drained = false;
+        } else if self.up && self.energy <= 0. {
+            self.drained = true;
+        }
+        self.up = raise && !self.drained && self.energy > 0.;
+        self.up != was_up
+    }
+}
+
+pub(crate) fn reset_shield_system(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(Shield::new(&settings));
+}
+
+pub(crate) fn shield_system(
+    mut shield: ResMut<Shield>,
+    controller_query: Query<Entity, (With<Player>, With<ShadowController>)>,
+    mut sprite_query: Query<&mut Sprite, With<Player>>,
+    mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>,
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    // a dead player has no use for the shield
+    let raise = actions.pressed(InputAction::Shield) && controller_query.iter().next().is_some();
+    if !shield.update(raise, time.delta(), &settings) {
+        return;
+    }
+
+    let tint = if shield.is_up() {
+        log::trace!("shield up");
+        sfx_event.send(LoopSfx::new(SoundAsset::Shield).into());
+        SHIELD_TINT
+    } else {
+        log::trace!("shield down");
+        sfx_event.send(StopSfx::new(SoundAsset::Shield).into());
+        Color::WHITE
+    };
+    // keep the opacity, as the player might be jumping through hyperspace
+    for mut sprite in sprite_query.iter_mut() {
+        let alpha = sprite.color.a();
+        sprite.color = tint;
+        sprite.color.set_a(alpha);
+    }
+}
+
+pub(crate) fn stop_shield_sound_system(mut sfx_event: EventWriter<SfxCmdEvent<SoundAsset>>) {
+    sfx_event.send(StopSfx::new(SoundAsset::Shield).into());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shield_drains_while_up_and_recharges_while_down() {
+        let settings = Settings::default();
+        let capacity = settings.player.shield_capacity;
+        let mut shield = Shield::new(&settings);
+        assert_eq!(shield.fraction(&settings), 1.);
+
+        assert!(shield.update(true, Duration::ZERO, &settings));
+        assert!(shield.is_up());
+        assert!(!shield.update(true, capacity / 2, &settings));
+        assert!((shield.fraction(&settings) - 0.5).abs() < 1e-3);
+
+        // drained, the shield drops even though it is still asked for
+        assert!(shield.update(true, capacity, &settings));
+        assert!(!shield.is_up());
+        assert_eq!(shield.fraction(&settings), 0.);
+
+        // holding on past drained keeps it down while recharging, instead of flickering
+        for _ in 0..10 {
+            assert!(!shield.update(true, capacity / 100, &settings));
+            assert!(!shield.is_up());
+        }
+        assert!(shield.fraction(&settings) > 0.);
+
+        // until asked for anew
+        assert!(!shield.update(false, Duration::ZERO, &settings));
+        assert!(shield.update(true, Duration::ZERO, &settings));
+        assert!(shield.update(false, Duration::ZERO, &settings));
+
+        let recharge = capacity.div_f32(settings.player.shield_recharge_rate);
+        assert!(!shield.update(false, recharge * 2, &settings));
+        assert_eq!(shield.fraction(&settings), 1.);
+    }
+}
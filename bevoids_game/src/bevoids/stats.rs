@@ -0,0 +1,311 @@
+use bevy::{log, prelude::*};
+use itertools::Itertools;
+use serde_derive::{Deserialize, Serialize};
+use serde_with::{serde_as, DurationSecondsWithFrac};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    bevoids::files::{move_to_backup, write_atomically},
+    bounds::GfxBounds,
+};
+
+use super::{
+    asteroids::{Asteroid, AsteroidCounter, AsteroidShotEvent},
+    laser::LaserSpawnedEvent,
+    movement::{ShadowOf, Velocity},
+    player::{Player, PlayerDeadEvent},
+    settings::Settings,
+};
+
+/// Statistics of a single run, from start to game over.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct RunStats {
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub duration: Duration,
+    pub asteroids_shot: usize,
+    pub asteroids_spawned: usize,
+    pub lasers_fired: usize,
+    pub max_speed: f32,
+}
+
+impl RunStats {
+    /// Fraction of the lasers fired that hit an asteroid.
+    pub fn accuracy(&self) -> f32 {
+        if self.lasers_fired == 0 {
+            0.
+        } else {
+            (self.asteroids_shot as f32 / self.lasers_fired as f32).min(1.)
+        }
+    }
+}
+
+pub(crate) fn reset_run_stats_system(mut commands: Commands) {
+    commands.insert_resource(RunStats::default());
+}
+
+pub(crate) fn update_run_stats_system(
+    mut stats: ResMut<RunStats>,
+    mut laser_spawned_events: EventReader<LaserSpawnedEvent>,
+    counter: Res<AsteroidCounter>,
+    player_query: Query<&Velocity, With<Player>>,
+    time: Res<Time>,
+) {
+    stats.duration += time.delta();
+    stats.lasers_fired += laser_spawned_events.iter().count();
+    stats.asteroids_shot = counter.shot();
+    stats.asteroids_spawned = counter.spawned();
+    for velocity in player_query.iter() {
+        stats.max_speed = stats.max_speed.max(velocity.length());
+    }
+}
+
+/// Size class of an asteroid, splitting the range of asteroid sizes in three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SizeClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl SizeClass {
+    #[must_use]
+    pub fn of(size: f32, settings: &Settings) -> Self {
+        let (min, max) = (settings.asteroid.size_min, settings.asteroid.size_max);
+        let fraction = (size - min) / (max - min);
+        if fraction < 1. / 3. {
+            Self::Small
+        } else if fraction < 2. / 3. {
+            Self::Medium
+        } else {
+            Self::Large
+        }
+    }
+}
+
+/// Number of asteroids destroyed, by size class.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct AsteroidsDestroyed {
+    pub small: usize,
+    pub medium: usize,
+    pub large: usize,
+}
+
+impl AsteroidsDestroyed {
+    pub fn add(&mut self, class: SizeClass) {
+        match class {
+            SizeClass::Small => self.small += 1,
+            SizeClass::Medium => self.medium += 1,
+            SizeClass::Large => self.large += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.small + self.medium + self.large
+    }
+}
+
+/// Statistics accumulated over all runs, kept next to the highscores - or only in memory,
+/// without a highscores file.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub(crate) struct LifetimeStats {
+    pub games_played: usize,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub play_time: Duration,
+    pub asteroids_destroyed: AsteroidsDestroyed,
+    pub lasers_fired: usize,
+    pub deaths: usize,
+    #[serde_as(as = "DurationSecondsWithFrac<f64>")]
+    pub longest_survival: Duration,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl LifetimeStats {
+    /// Reads the statistics kept in `file`, starting from scratch if they cannot be read.
+    #[must_use]
+    pub fn load(file: Option<PathBuf>) -> Self {
+        let mut stats = file.as_deref().map(Self::read).unwrap_or_default();
+        stats.file = file;
+        stats
+    }
+
+    /// Reads the statistics kept in `path`, moving a corrupt file aside to a backup rather than
+    /// having the next save overwrite it.
+    fn read(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+                match move_to_backup(path) {
+                    Ok(backup) => {
+                        log::warn!(?path, ?backup, "moved corrupt statistics aside: {}", err)
+                    }
+                    Err(backup_err) => {
+                        log::warn!(
+                            ?path,
+                            "unable to back up corrupt statistics: {}",
+                            backup_err
+                        )
+                    }
+                }
+                Self::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                log::warn!(?path, "unable to read statistics: {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Some(path) = &self.file {
+            let content = serde_json::to_string_pretty(self).expect("statistics are serializable");
+            let result = match path.parent() {
+                Some(dir) => fs::create_dir_all(dir).and_then(|_| write_atomically(path, content)),
+                None => write_atomically(path, content),
+            };
+            if let Err(err) = result {
+                log::warn!(?path, "unable to save statistics: {}", err);
+            }
+        }
+    }
+
+    /// Fraction of all lasers fired that hit an asteroid.
+    pub fn accuracy(&self) -> f32 {
+        if self.lasers_fired == 0 {
+            0.
+        } else {
+            (self.asteroids_destroyed.total() as f32 / self.lasers_fired as f32).min(1.)
+        }
+    }
+
+    /// Takes account of a run ending after `duration`.
+    pub fn record_death(&mut self, duration: Duration) {
+        self.deaths += 1;
+        self.longest_survival = self.longest_survival.max(duration);
+    }
+}
+
+pub(crate) fn count_game_played_system(mut stats: ResMut<LifetimeStats>) {
+    stats.games_played += 1;
+}
+
+/// Accumulates the lifetime statistics from the events of the run.
+pub(crate) fn update_lifetime_stats_system(
+    mut stats: ResMut<LifetimeStats>,
+    mut laser_spawned_events: EventReader<LaserSpawnedEvent>,
+    mut shot_events: EventReader<AsteroidShotEvent>,
+    mut dead_events: EventReader<PlayerDeadEvent>,
+    bounds_query: Query<&GfxBounds, With<Asteroid>>,
+    shadowof_query: Query<&ShadowOf, With<Asteroid>>,
+    run_stats: Res<RunStats>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    stats.play_time += time.delta();
+    stats.lasers_fired += laser_spawned_events.iter().count();
+
+    // a shot shadow counts as its asteroid
+    for bounds in shot_events
+        .iter()
+        .map(|event| match shadowof_query.get(**event) {
+            Ok(shadow) => shadow.controller,
+            Err(_) => **event,
+        })
+        .unique()
+        .filter_map(|asteroid| bounds_query.get(asteroid).ok())
+    {
+        let class = SizeClass::of(bounds.size().max_element(), &settings);
+        stats.asteroids_destroyed.add(class);
+    }
+
+    // the player and its shadows colliding in the same frame is a single death
+    if dead_events.iter().count() > 0 {
+        stats.record_death(run_stats.duration);
+    }
+}
+
+/// Saves the lifetime statistics once the run is over - or paused, the game might be closed
+/// from there.
+pub(crate) fn save_lifetime_stats_system(stats: Res<LifetimeStats>) {
+    stats.save();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bevoids::files::TestDir;
+
+    #[test]
+    fn accuracy_is_hits_per_laser() {
+        let stats = RunStats {
+            asteroids_shot: 3,
+            lasers_fired: 4,
+            ..RunStats::default()
+        };
+        assert_eq!(stats.accuracy(), 0.75);
+        assert_eq!(RunStats::default().accuracy(), 0.);
+    }
+
+    #[test]
+    fn asteroids_are_classed_by_size() {
+        let settings = Settings::default();
+        let (min, max) = (settings.asteroid.size_min, settings.asteroid.size_max);
+        assert_eq!(SizeClass::of(min, &settings), SizeClass::Small);
+        assert_eq!(
+            SizeClass::of((min + max) / 2., &settings),
+            SizeClass::Medium
+        );
+        assert_eq!(SizeClass::of(max, &settings), SizeClass::Large);
+    }
+
+    #[test]
+    fn lifetime_stats_keep_the_longest_survival() {
+        let mut stats = LifetimeStats::default();
+        stats.record_death(Duration::from_secs(20));
+        stats.record_death(Duration::from_secs(10));
+        assert_eq!(stats.deaths, 2);
+        assert_eq!(stats.longest_survival, Duration::from_secs(20));
+
+        stats.lasers_fired = 4;
+        stats.asteroids_destroyed.add(SizeClass::Large);
+        stats.asteroids_destroyed.add(SizeClass::Small);
+        assert_eq!(stats.accuracy(), 0.5);
+    }
+
+    #[test]
+    fn lifetime_stats_survive_a_round_trip() {
+        let dir = TestDir::new();
+        let file = dir.join("statistics.json");
+        let mut stats = LifetimeStats::load(Some(file.clone()));
+        stats.games_played = 3;
+        stats.play_time = Duration::from_millis(1500);
+        stats.asteroids_destroyed.add(SizeClass::Medium);
+        stats.save();
+
+        assert_eq!(LifetimeStats::load(Some(file)), stats);
+    }
+
+    #[test]
+    fn corrupt_lifetime_stats_are_moved_aside() {
+        let dir = TestDir::new();
+        let file = dir.join("statistics.json");
+        fs::write(&file, "{ not json").unwrap();
+
+        let mut stats = LifetimeStats::load(Some(file.clone()));
+        assert_eq!(stats.games_played, 0);
+        stats.games_played = 1;
+        stats.save();
+
+        assert_eq!(LifetimeStats::load(Some(file)).games_played, 1);
+        let backups = dir.backups();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "{ not json");
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2, Color32, Label, RichText},
+    EguiContext,
+};
+
+use crate::bevoids::{
+    achievement::AchievementToasts, profile::Profiles, settings::Settings, GameState,
+};
+
+pub(crate) fn display_achievements_menu_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut started: Local<bool>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut hint: String = "".to_string();
+    let profile = profiles.active();
+
+    egui::Window::new("Achievements Menu")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::top_down_justified(egui::Align::Center),
+                |ui| {
+                    ui.add(Label::new(
+                        RichText::new("Achievements")
+                            .heading()
+                            .color(Color32::WHITE),
+                    ));
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
+                    egui::Grid::new("Achievements Grid")
+                        .num_columns(2)
+                        .spacing([20., 4.])
+                        .show(ui, |ui| {
+                            for achievement in &settings.achievements {
+                                let unlocked = profile
+                                    .filter(|profile| profile.has_unlocked(&achievement.name))
+                                    .is_some();
+                                let color = if unlocked {
+                                    Color32::WHITE
+                                } else {
+                                    Color32::GRAY
+                                };
+                                ui.vertical(|ui| {
+                                    ui.add(Label::new(
+                                        RichText::new(&achievement.name).color(color),
+                                    ));
+                                    ui.add(Label::new(
+                                        RichText::new(&achievement.description)
+                                            .small()
+                                            .color(Color32::LIGHT_BLUE),
+                                    ));
+                                });
+                                let status = if unlocked { "Unlocked" } else { "Locked" };
+                                ui.add(Label::new(RichText::new(status).small().color(color)));
+                                ui.end_row();
+                            }
+                        });
+
+                    if profile.is_none() {
+                        ui.add(egui::Separator::default().horizontal().spacing(20.));
+                        ui.add(Label::new(
+                            RichText::new("Pick a player to earn achievements")
+                                .small()
+                                .color(Color32::YELLOW),
+                        ));
+                    }
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let back_button = ui.button("Back");
+                    if back_button.clicked() {
+                        *started = false;
+                        state.pop().unwrap();
+                    }
+
+                    if back_button.has_focus() {
+                        hint = "Hit Escape or Enter to go back".to_string();
+                    } else if !*started {
+                        *started = true;
+                        back_button.request_focus();
+                    }
+                },
+            );
+        });
+
+    egui::Window::new("Achievements Hint")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-5., -5.])
+        .show(ctx, |ui| {
+            ui.add(egui::Label::new(RichText::new(hint).small()));
+        });
+}
+
+pub(crate) fn display_achievement_toasts_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut toasts: ResMut<AchievementToasts>,
+    time: Res<Time>,
+) {
+    toasts.tick(time.delta());
+    if toasts.iter().next().is_none() {
+        return;
+    }
+
+    egui::Window::new("Achievement Toasts")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_TOP, [0., 40.])
+        .show(egui_context.ctx_mut(), |ui| {
+            for toast in toasts.iter() {
+                ui.add(Label::new(
+                    RichText::new(format!("Achievement unlocked: {}", toast.name))
+                        .color(Color32::GOLD),
+                ));
+                ui.add(Label::new(
+                    RichText::new(&toast.description)
+                        .small()
+                        .color(Color32::LIGHT_BLUE),
+                ));
+            }
+        });
+}
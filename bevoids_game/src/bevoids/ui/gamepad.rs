@@ -0,0 +1,36 @@
+use bevy::{prelude::*, utils::HashMap, window::WindowId};
+use bevy_egui::{egui, EguiInput};
+
+/// Lets gamepads navigate the menus, by handing egui the keys used for keyboard navigation.
+///
+/// Must run after [bevy_egui::EguiSystem::ProcessInput] and before
+/// [bevy_egui::EguiSystem::BeginFrame].
+pub(crate) fn gamepad_menu_navigation_system(
+    buttons: Res<Input<GamepadButton>>,
+    mut egui_input: ResMut<HashMap<WindowId, EguiInput>>,
+) {
+    let egui_input = match egui_input.get_mut(&WindowId::primary()) {
+        Some(egui_input) => egui_input,
+        None => return,
+    };
+
+    for button in buttons.get_just_pressed() {
+        let (key, shift) = match button.1 {
+            GamepadButtonType::DPadDown => (egui::Key::Tab, false),
+            GamepadButtonType::DPadUp => (egui::Key::Tab, true),
+            GamepadButtonType::South => (egui::Key::Enter, false),
+            _ => continue,
+        };
+        let modifiers = egui::Modifiers {
+            shift,
+            ..Default::default()
+        };
+        for pressed in [true, false] {
+            egui_input.raw_input.events.push(egui::Event::Key {
+                key,
+                pressed,
+                modifiers,
+            });
+        }
+    }
+}
@@ -1,19 +1,38 @@
 use bevoids_assets::SpriteAsset;
-use bevy::prelude::*;
+use bevy::{log, prelude::*, tasks::IoTaskPool};
 use bevy_egui::{
     egui::{self, Align2, Color32, Label, RichText, ScrollArea},
     EguiContext,
 };
 
-use crate::bevoids::{highscore::HighScoreRepository, GameState};
+use crate::bevoids::{
+    highscore::{
+        all_boards, default_path, export, import, BoardCapacities, BoardKey, GameMode,
+        HighScoreBoards, HighScoreNotice, HighScoreService, HighScoreWarning, PlayerRank,
+    },
+    settings::{Settings, UnverifiedHighScores},
+    stats::RunStats,
+    GameState,
+};
 
 const TROPHY_TEXTURE_ID: u64 = 0;
 
 pub(crate) fn display_highscore_menu_system(
+    mut commands: Commands,
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<GameState>>,
-    highscores: Res<HighScoreRepository>,
+    boards: Res<HighScoreBoards>,
+    player_rank: Option<Res<PlayerRank>>,
+    notice: Option<Res<HighScoreNotice>>,
+    service: Res<HighScoreService>,
+    pool: Res<IoTaskPool>,
+    settings: Res<Settings>,
+    kb: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
     mut started: Local<bool>,
+    mut selected: Local<Option<BoardKey>>,
+    mut expanded: Local<Option<usize>>,
+    mut transfer_path: Local<Option<String>>,
     assets: Res<AssetServer>,
 ) {
     let mut hint: String = "".to_string();
@@ -21,8 +40,47 @@ pub(crate) fn display_highscore_menu_system(
     if !*started {
         let texture_handle = assets.load(SpriteAsset::GfxTrophy);
         egui_context.set_egui_texture(TROPHY_TEXTURE_ID, texture_handle);
+        commands.remove_resource::<HighScoreNotice>();
+
+        // show the board of the last highscore, or of the selected difficulty
+        *selected = Some(player_rank.as_ref().map_or_else(
+            || BoardKey::new(GameMode::Classic, &settings.general.difficulty),
+            |player_rank| player_rank.board.clone(),
+        ));
     }
 
+    let mut keys: Vec<BoardKey> = BoardKey::all(&settings)
+        .chain(boards.keys().cloned())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    let selected_index = selected
+        .as_ref()
+        .and_then(|key| keys.iter().position(|k| k == key))
+        .unwrap_or(0);
+    // arrow keys move the cursor while typing the export path, rather than switching board
+    let typing = egui_context.ctx_mut().wants_keyboard_input();
+    let step = if (!typing && kb.just_pressed(KeyCode::Left))
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.1 == GamepadButtonType::DPadLeft)
+    {
+        keys.len() - 1
+    } else if (!typing && kb.just_pressed(KeyCode::Right))
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.1 == GamepadButtonType::DPadRight)
+    {
+        1
+    } else {
+        0
+    };
+    if step != 0 {
+        *selected = keys.get((selected_index + step) % keys.len()).cloned();
+        *expanded = None;
+    }
+    let board = selected.as_ref().and_then(|key| boards.board(key));
+
     egui::Window::new("HighScore Menu")
         .resizable(false)
         .title_bar(false)
@@ -57,20 +115,28 @@ pub(crate) fn display_highscore_menu_system(
 
                     ui.add(egui::Separator::default().horizontal().spacing(20.));
 
+                    ui.horizontal_wrapped(|ui| {
+                        for key in &keys {
+                            if ui
+                                .selectable_label(selected.as_ref() == Some(key), key.to_string())
+                                .clicked()
+                            {
+                                *selected = Some(key.clone());
+                                *expanded = None;
+                            }
+                        }
+                    });
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
                     let row_height = ui.fonts()[egui::TextStyle::Body].row_height();
-                    let num_rows = highscores.count();
                     ScrollArea::vertical()
                         .max_height(row_height * 11. + 4.)
-                        .show_rows(ui, row_height, num_rows, |ui, row_range| {
-                            for (n, highscore) in highscores
-                                .iter()
-                                .skip(row_range.start)
-                                .take(row_range.end - row_range.start)
-                                .enumerate()
-                            {
+                        .show(ui, |ui| {
+                            for (n, highscore) in board.iter().flat_map(|b| b.iter()).enumerate() {
                                 ui.horizontal(|ui| {
                                     ui.add(Label::new(
-                                        RichText::new(format!("{: >3}", 1 + n + row_range.start))
+                                        RichText::new(format!("{: >3}", 1 + n))
                                             .small()
                                             .color(Color32::LIGHT_BLUE),
                                     ));
@@ -84,10 +150,99 @@ pub(crate) fn display_highscore_menu_system(
                                             .monospace()
                                             .color(Color32::WHITE),
                                     ));
+                                    if highscore.unverified() {
+                                        ui.add(Label::new(
+                                            RichText::new("unverified")
+                                                .small()
+                                                .color(Color32::YELLOW),
+                                        ))
+                                        .on_hover_text("The highscores file has been edited");
+                                    }
+                                    if highscore.stats().is_some() {
+                                        let is_expanded = *expanded == Some(n);
+                                        let toggle = if is_expanded { "-" } else { "+" };
+                                        if ui.small_button(toggle).clicked() {
+                                            *expanded = if is_expanded { None } else { Some(n) };
+                                        }
+                                    }
                                 });
+                                if let (Some(stats), true) =
+                                    (highscore.stats(), *expanded == Some(n))
+                                {
+                                    ui.add(Label::new(
+                                        RichText::new(format_stats(stats))
+                                            .small()
+                                            .color(Color32::LIGHT_BLUE),
+                                    ));
+                                }
                             }
                         });
 
+                    if board.filter(|board| board.count() > 0).is_none() {
+                        ui.add(Label::new(
+                            RichText::new("No highscores yet").color(Color32::LIGHT_BLUE),
+                        ));
+                    }
+
+                    if let Some(player_rank) = player_rank
+                        .as_ref()
+                        .filter(|player_rank| selected.as_ref() == Some(&player_rank.board))
+                    {
+                        let text = match player_rank.rank {
+                            Some(rank) => format!("{} is ranked #{}", player_rank.name, rank + 1),
+                            None => format!("{} is not on the board", player_rank.name),
+                        };
+                        ui.add(egui::Separator::default().horizontal().spacing(20.));
+                        ui.add(Label::new(
+                            RichText::new(text).small().color(Color32::LIGHT_BLUE),
+                        ));
+                    }
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let path = transfer_path
+                        .get_or_insert_with(|| default_path().to_string_lossy().into_owned());
+                    ui.horizontal(|ui| {
+                        let export_button = ui.button("Export");
+                        let import_button = ui.button("Import");
+                        ui.text_edit_singleline(path);
+                        if export_button.has_focus() || import_button.has_focus() {
+                            hint = "Export to, or import from, a .csv or .json file".to_string();
+                        }
+                        if export_button.clicked() {
+                            let notice = match export(&boards, path.as_ref()) {
+                                Ok(count) => format!("Exported {} highscores", count),
+                                Err(err) => format!("Export failed: {}", err),
+                            };
+                            commands.insert_resource(HighScoreNotice(notice));
+                        }
+                        if import_button.clicked() {
+                            match import(path.as_ref()) {
+                                Ok(mut highscores) => {
+                                    if settings.general.unverified_highscores
+                                        == UnverifiedHighScores::Drop
+                                    {
+                                        highscores.retain(|highscore| !highscore.unverified());
+                                    }
+                                    let capacities = BoardCapacities::from(&*settings);
+                                    let boards = all_boards(&settings, &capacities);
+                                    service.import(&pool, highscores, boards);
+                                }
+                                Err(err) => {
+                                    log::warn!("unable to import highscores: {}", err);
+                                    commands.insert_resource(HighScoreNotice(format!(
+                                        "Import failed: {}",
+                                        err
+                                    )));
+                                }
+                            }
+                        }
+                    });
+                    if let Some(notice) = &notice {
+                        ui.add(Label::new(
+                            RichText::new(&notice.0).small().color(Color32::LIGHT_BLUE),
+                        ));
+                    }
+
                     ui.add(egui::Separator::default().horizontal().spacing(20.));
                     let mainmenu_button = ui.button("Main Menu");
 
@@ -97,10 +252,11 @@ pub(crate) fn display_highscore_menu_system(
                     }
 
                     if mainmenu_button.has_focus() {
-                        hint = "Hit Enter for main menu".to_string();
+                        hint = "Hit Enter for main menu, Left/Right to switch board".to_string();
                     }
                     if mainmenu_button.clicked() {
                         *started = false;
+                        *expanded = None;
                         state.set(GameState::MainMenu).unwrap();
                     }
                 },
@@ -115,3 +271,35 @@ pub(crate) fn display_highscore_menu_system(
             ui.add(egui::Label::new(RichText::new(hint).small()));
         });
 }
+
+fn format_stats(stats: &RunStats) -> String {
+    let seconds = stats.duration.as_secs();
+    format!(
+        "{}:{:02} played, {} of {} asteroids shot, {} lasers fired ({:.0}% accuracy), top speed {:.0}",
+        seconds / 60,
+        seconds % 60,
+        stats.asteroids_shot,
+        stats.asteroids_spawned,
+        stats.lasers_fired,
+        stats.accuracy() * 100.,
+        stats.max_speed
+    )
+}
+
+pub(crate) fn display_highscore_warning_system(
+    mut egui_context: ResMut<EguiContext>,
+    state: Res<State<GameState>>,
+    warning: Option<Res<HighScoreWarning>>,
+) {
+    if let (Some(warning), false) = (warning, *state.current() == GameState::Playing) {
+        egui::Window::new("HighScore Warning")
+            .resizable(false)
+            .title_bar(false)
+            .anchor(Align2::CENTER_TOP, [0., 5.])
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.add(Label::new(
+                    RichText::new(&warning.0).small().color(Color32::YELLOW),
+                ));
+            });
+    }
+}
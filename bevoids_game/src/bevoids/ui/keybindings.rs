@@ -0,0 +1,169 @@
+use bevy::{log, prelude::*};
+use bevy_egui::{
+    egui::{self, Align2, Color32, Label, RichText},
+    EguiContext,
+};
+use serde_json::json;
+
+use crate::bevoids::{
+    profile::ProfileSettingsChangedEvent,
+    settings::{update_user_settings, BaseSettings, KeyCodes},
+    GameState,
+};
+
+#[derive(Default)]
+pub(crate) struct KeyBindingsMenu {
+    started: bool,
+    capturing: Option<&'static str>,
+}
+
+pub(crate) fn display_keybindings_menu_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    mut settings: ResMut<BaseSettings>,
+    mut kb: ResMut<Input<KeyCode>>,
+    mut menu: Local<KeyBindingsMenu>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut hint: String = "".to_string();
+    let mut keycodes = settings.keycodes.clone();
+
+    // buttons stay disabled during the frame a key is captured, so the key doesn't also press them
+    let capturing = menu.capturing.is_some();
+    if let Some(action) = menu.capturing {
+        let pressed = kb.get_just_pressed().next().copied();
+        if let Some(key) = pressed {
+            if key != KeyCode::Escape {
+                if let Some((_, keys)) = keycodes
+                    .bindings_mut()
+                    .into_iter()
+                    .find(|(name, _)| *name == action)
+                {
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+            }
+            kb.reset(key);
+            menu.capturing = None;
+            menu.started = false;
+        }
+    } else if kb.just_pressed(KeyCode::Escape) {
+        kb.reset(KeyCode::Escape);
+        menu.started = false;
+        state.pop().unwrap();
+    }
+
+    egui::Window::new("KeyBindings Menu")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::top_down_justified(egui::Align::Center),
+                |ui| {
+                    ui.add(Label::new(
+                        RichText::new("Key Bindings")
+                            .heading()
+                            .color(Color32::WHITE),
+                    ));
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
+                    egui::Grid::new("KeyBindings Grid")
+                        .num_columns(4)
+                        .spacing([20., 8.])
+                        .show(ui, |ui| {
+                            for (action, keys) in keycodes.bindings_mut() {
+                                ui.label(action.replace('_', " "));
+                                if menu.capturing == Some(action) {
+                                    ui.label(
+                                        RichText::new("press a key...").color(Color32::YELLOW),
+                                    );
+                                } else {
+                                    ui.label(
+                                        keys.iter()
+                                            .map(|key| format!("{:?}", key))
+                                            .collect::<Vec<_>>()
+                                            .join(", "),
+                                    );
+                                }
+                                let add_button =
+                                    ui.add_enabled(!capturing, egui::Button::new("Add"));
+                                if add_button.clicked() {
+                                    menu.capturing = Some(action);
+                                }
+                                let clear_button =
+                                    ui.add_enabled(!capturing, egui::Button::new("Clear"));
+                                if clear_button.clicked() {
+                                    keys.clear();
+                                }
+
+                                if add_button.has_focus() {
+                                    hint =
+                                        format!("Hit Enter, then a key to bind it to {}", action);
+                                } else if clear_button.has_focus() {
+                                    hint = format!("Hit Enter to unbind all keys from {}", action);
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                    for conflict in keycodes.conflicts() {
+                        ui.add(Label::new(
+                            RichText::new(format!(
+                                "{:?} is bound to both {} and {}",
+                                conflict.key, conflict.first, conflict.second
+                            ))
+                            .color(Color32::YELLOW),
+                        ));
+                    }
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let reset_button =
+                        ui.add_enabled(!capturing, egui::Button::new("Reset to defaults"));
+                    if reset_button.clicked() {
+                        keycodes = KeyCodes::default();
+                    }
+                    let back_button = ui.add_enabled(!capturing, egui::Button::new("Back"));
+                    if back_button.clicked() {
+                        menu.started = false;
+                        menu.capturing = None;
+                        state.pop().unwrap();
+                    }
+
+                    if menu.capturing.is_some() {
+                        hint = "Press the key to bind, or Escape to cancel".to_string();
+                    } else if reset_button.has_focus() {
+                        hint = "Hit Enter to restore the default key bindings".to_string();
+                    } else if back_button.has_focus() {
+                        hint = "Hit Escape or Enter to go back".to_string();
+                    } else if !menu.started && !capturing {
+                        menu.started = true;
+                        back_button.request_focus();
+                    }
+                },
+            );
+        });
+
+    egui::Window::new("KeyBindings Hint")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-5., -5.])
+        .show(ctx, |ui| {
+            ui.add(egui::Label::new(RichText::new(hint).small()));
+        });
+
+    if keycodes != settings.keycodes {
+        settings.keycodes = keycodes;
+    }
+}
+
+pub(crate) fn save_keybindings_system(
+    settings: Res<BaseSettings>,
+    mut changed_event: EventWriter<ProfileSettingsChangedEvent>,
+) {
+    changed_event.send(ProfileSettingsChangedEvent);
+    if let Err(err) = update_user_settings(json!({ "keycodes": settings.keycodes })) {
+        log::error!("unable to save key bindings: {}", err);
+    }
+}
@@ -1,15 +1,24 @@
-use bevy::{app::AppExit, prelude::*};
+use bevy::{app::AppExit, log, prelude::*};
 use bevy_egui::{
     egui::{self, Align2, Color32, Label, RichText},
     EguiContext,
 };
 
-use crate::bevoids::GameState;
+use serde_json::json;
+
+use crate::bevoids::{
+    profile::{ProfileSettingsChangedEvent, Profiles},
+    settings::{update_user_settings, BaseSettings},
+    GameState,
+};
 
 pub(crate) fn display_main_menu_system(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<GameState>>,
     mut exit: EventWriter<AppExit>,
+    mut settings: ResMut<BaseSettings>,
+    mut changed_event: EventWriter<ProfileSettingsChangedEvent>,
+    profiles: Res<Profiles>,
     mut started: Local<bool>,
 ) {
     let ctx = egui_context.ctx_mut();
@@ -33,11 +42,38 @@ pub(crate) fn display_main_menu_system(
                         *started = false;
                         state.set(GameState::Playing).unwrap();
                     }
+                    let player = profiles.active().map_or("none", |profile| &profile.name);
+                    let player_button = ui.button(format!("Player: {}", player));
+                    if player_button.clicked() {
+                        *started = false;
+                        state.push(GameState::Profiles).unwrap();
+                    }
+                    let difficulty_button =
+                        ui.button(format!("Difficulty: {}", settings.general.difficulty));
+                    if difficulty_button.clicked() {
+                        select_next_difficulty(&mut settings);
+                        changed_event.send(ProfileSettingsChangedEvent);
+                    }
                     let highscore_button = ui.button("Highscores");
                     if highscore_button.clicked() {
                         *started = false;
                         state.set(GameState::HighScoreMenu).unwrap();
                     }
+                    let statistics_button = ui.button("Statistics");
+                    if statistics_button.clicked() {
+                        *started = false;
+                        state.push(GameState::Statistics).unwrap();
+                    }
+                    let achievements_button = ui.button("Achievements");
+                    if achievements_button.clicked() {
+                        *started = false;
+                        state.push(GameState::Achievements).unwrap();
+                    }
+                    let options_button = ui.button("Options");
+                    if options_button.clicked() {
+                        *started = false;
+                        state.push(GameState::Options).unwrap();
+                    }
                     let exit_button = ui.button("Exit");
                     if exit_button.clicked() {
                         exit.send(AppExit);
@@ -45,8 +81,18 @@ pub(crate) fn display_main_menu_system(
 
                     if start_button.has_focus() {
                         hint = "Hit Enter to play".to_string();
+                    } else if player_button.has_focus() {
+                        hint = "Hit Enter to pick who is playing".to_string();
+                    } else if difficulty_button.has_focus() {
+                        hint = "Hit Enter to change difficulty".to_string();
                     } else if highscore_button.has_focus() {
                         hint = "Hit Enter to view highscores".to_string();
+                    } else if statistics_button.has_focus() {
+                        hint = "Hit Enter to view statistics".to_string();
+                    } else if achievements_button.has_focus() {
+                        hint = "Hit Enter to view achievements".to_string();
+                    } else if options_button.has_focus() {
+                        hint = "Hit Enter to change options".to_string();
                     } else if exit_button.has_focus() {
                         hint = "Hit Enter to exit".to_string();
                     } else if !*started {
@@ -65,3 +111,18 @@ pub(crate) fn display_main_menu_system(
             ui.add(egui::Label::new(RichText::new(hint).small()));
         });
 }
+
+fn select_next_difficulty(settings: &mut BaseSettings) {
+    let difficulties = &settings.difficulties;
+    let next = difficulties
+        .iter()
+        .position(|d| d.name == settings.general.difficulty)
+        .map_or(0, |index| (index + 1) % difficulties.len());
+    settings.general.difficulty = difficulties[next].name.clone();
+
+    if let Err(err) = update_user_settings(json!({
+        "general": { "difficulty": settings.general.difficulty }
+    })) {
+        log::error!("unable to save difficulty: {}", err);
+    }
+}
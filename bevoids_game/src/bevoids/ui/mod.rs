@@ -1,13 +1,25 @@
+mod achievements;
 mod gameover;
+mod gamepad;
 mod highscore;
+mod keybindings;
 mod main;
 mod new_highscore;
+mod options;
 mod paused;
 mod playing;
+mod profiles;
+mod statistics;
 
+pub(crate) use achievements::*;
 pub(crate) use gameover::*;
+pub(crate) use gamepad::*;
 pub(crate) use highscore::*;
+pub(crate) use keybindings::*;
 pub(crate) use main::*;
 pub(crate) use new_highscore::*;
+pub(crate) use options::*;
 pub(crate) use paused::*;
 pub(crate) use playing::*;
+pub(crate) use profiles::*;
+pub(crate) use statistics::*;
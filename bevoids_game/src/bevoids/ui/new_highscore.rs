@@ -1,33 +1,61 @@
 use bevoids_assets::SpriteAsset;
-use bevy::prelude::*;
+use bevy::{prelude::*, tasks::IoTaskPool};
 use bevy_egui::{
     egui::{self, RichText},
     EguiContext,
 };
 
 use crate::bevoids::{
-    highscore::{save_highscores, HighScore, HighScoreRepository, Score},
+    highscore::{BoardCapacities, HighScore, HighScoreBoards, HighScoreService, Score},
+    profile::Profiles,
+    settings::Settings,
+    stats::RunStats,
     GameState,
 };
 
 const TROPHY_TEXTURE_ID: u64 = 0;
+const GAMEPAD_LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
 
 pub(crate) fn display_new_highscore_menu_system(
     mut egui_context: ResMut<EguiContext>,
     score: Res<Score>,
+    stats: Res<RunStats>,
     //textures: Res<TextureAssetMap<GeneralTexture>>,
     mut state: ResMut<State<GameState>>,
     mut name: Local<String>,
-    mut highscore_repo: ResMut<HighScoreRepository>,
+    mut highscore_boards: ResMut<HighScoreBoards>,
+    service: Res<HighScoreService>,
+    pool: Res<IoTaskPool>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
     mut kb: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
     mut started: Local<bool>,
     assets: Res<AssetServer>,
 ) {
     let mut hint: String = "".to_string();
 
+    for button in gamepad_buttons.get_just_pressed() {
+        match button.1 {
+            GamepadButtonType::DPadRight => name.push('A'),
+            GamepadButtonType::DPadLeft => {
+                name.pop();
+            }
+            GamepadButtonType::RightTrigger => cycle_last_letter(&mut name, 1),
+            GamepadButtonType::LeftTrigger => {
+                cycle_last_letter(&mut name, GAMEPAD_LETTERS.len() - 1)
+            }
+            _ => {}
+        }
+    }
+
     if !*started {
         let texture_handle = assets.load(SpriteAsset::GfxTrophy);
         egui_context.set_egui_texture(TROPHY_TEXTURE_ID, texture_handle);
+        if let (true, Some(profile)) = (name.is_empty(), profiles.active()) {
+            *name = profile.name.clone();
+        }
     }
 
     egui::Window::new("NewHighScore Menu")
@@ -57,29 +85,40 @@ pub(crate) fn display_new_highscore_menu_system(
                     let name_box =
                         ui.add(egui::TextEdit::singleline(&mut *name).hint_text("Enter your name"));
 
+                    if !*started {
+                        *started = true;
+                        name_box.request_focus();
+                    }
+
                     let trimmed_name = name.trim();
                     if trimmed_name.len() >= 3 {
                         hint = "Hit Enter accept".to_string();
                         ui.add(egui::Separator::default().horizontal().spacing(20.));
                         if ui.button("Enter hall of fame").clicked() || name_box.clicked() {
                             kb.reset(KeyCode::Return);
-                            highscore_repo
-                                .push(HighScore::new(*score, trimmed_name))
+                            let highscore =
+                                HighScore::new(*score, trimmed_name, &settings.general.difficulty)
+                                    .with_stats(*stats);
+                            let capacities = BoardCapacities::from(&*settings);
+                            let capacity = capacities.of(&highscore.key()) as usize;
+                            highscore_boards
+                                .push(highscore.clone(), &capacities)
                                 .expect("failed adding highscore");
                             name.clear();
 
-                            save_highscores(&highscore_repo);
+                            service.submit(&pool, highscore, capacity);
 
                             *started = false;
                             state.set(GameState::HighScoreMenu).unwrap();
                         }
                     } else {
-                        if !*started {
-                            *started = true;
-                            name_box.request_focus();
-                        }
                         hint = "At least 3 charaters required".to_string();
                     }
+                    if gamepads.iter().next().is_some() {
+                        hint.push_str(
+                            " - D-pad right/left adds/removes a letter, the bumpers change it",
+                        );
+                    }
                 },
             )
         });
@@ -92,3 +131,16 @@ pub(crate) fn display_new_highscore_menu_system(
             ui.add(egui::Label::new(RichText::new(hint).small()));
         });
 }
+
+/// Steps the last letter of `name` through [GAMEPAD_LETTERS], for typing without a keyboard.
+fn cycle_last_letter(name: &mut String, step: usize) {
+    let letter = match name.pop() {
+        Some(letter) => GAMEPAD_LETTERS
+            .find(letter.to_ascii_uppercase())
+            .map(|index| (index + step) % GAMEPAD_LETTERS.len())
+            .and_then(|index| GAMEPAD_LETTERS.chars().nth(index))
+            .unwrap_or('A'),
+        None => 'A',
+    };
+    name.push(letter);
+}
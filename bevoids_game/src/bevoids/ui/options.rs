@@ -0,0 +1,147 @@
+use bevy::{log, prelude::*};
+use bevy_egui::{
+    egui::{self, Align2, Color32, Label, RichText, Slider},
+    EguiContext,
+};
+use serde_json::json;
+
+use crate::bevoids::{
+    settings::{update_user_settings, BaseSettings, WindowMode},
+    GameState,
+};
+
+pub(crate) fn display_options_menu_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    mut settings: ResMut<BaseSettings>,
+    mut started: Local<bool>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut hint: String = "".to_string();
+
+    let mut volume = settings.volume.clone();
+    let mut window = settings.window.clone();
+    let (mut turn_speed_slow, mut turn_speed_fast) = (
+        settings.player.turn_speed_slow,
+        settings.player.turn_speed_fast,
+    );
+    let mut changed = false;
+
+    egui::Window::new("Options Menu")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::top_down_justified(egui::Align::Center),
+                |ui| {
+                    ui.add(Label::new(
+                        RichText::new("Options").heading().color(Color32::WHITE),
+                    ));
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
+                    egui::Grid::new("Options Grid")
+                        .num_columns(2)
+                        .spacing([20., 8.])
+                        .show(ui, |ui| {
+                            for (label, value) in [
+                                ("Laser", &mut volume.laser),
+                                ("Thruster", &mut volume.thruster),
+                                ("Ship explosion", &mut volume.ship_explosion),
+                                ("Asteroid explosion", &mut volume.asteroid_explosion),
+                                ("Shield", &mut volume.shield),
+                            ] {
+                                ui.label(label);
+                                changed |= ui.add(Slider::new(value, 0.0..=1.0)).changed();
+                                ui.end_row();
+                            }
+
+                            ui.label("Turn speed");
+                            changed |= ui
+                                .add(Slider::new(&mut turn_speed_slow, 0.1..=turn_speed_fast))
+                                .changed();
+                            ui.end_row();
+                            ui.label("Fast turn speed");
+                            changed |= ui
+                                .add(Slider::new(&mut turn_speed_fast, turn_speed_slow..=10.0))
+                                .changed();
+                            ui.end_row();
+
+                            ui.label("Window size");
+                            ui.horizontal(|ui| {
+                                changed |= ui
+                                    .add(
+                                        egui::DragValue::new(&mut window.width)
+                                            .clamp_range(320..=3840),
+                                    )
+                                    .changed();
+                                ui.label("x");
+                                changed |= ui
+                                    .add(
+                                        egui::DragValue::new(&mut window.height)
+                                            .clamp_range(240..=2160),
+                                    )
+                                    .changed();
+                            });
+                            ui.end_row();
+                            ui.label("Fullscreen");
+                            let mut fullscreen = window.mode == WindowMode::Fullscreen;
+                            if ui.checkbox(&mut fullscreen, "").changed() {
+                                window.mode = window.mode.toggled();
+                                changed = true;
+                            }
+                            ui.end_row();
+                        });
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let keybindings_button = ui.button("Key bindings");
+                    if keybindings_button.clicked() {
+                        *started = false;
+                        state.push(GameState::KeyBindings).unwrap();
+                    }
+                    let back_button = ui.button("Back");
+                    if back_button.clicked() {
+                        *started = false;
+                        state.pop().unwrap();
+                    }
+
+                    if keybindings_button.has_focus() {
+                        hint = "Hit Enter to change the key bindings".to_string();
+                    } else if back_button.has_focus() {
+                        hint = "Hit Escape or Enter to go back".to_string();
+                    } else if !*started {
+                        *started = true;
+                        back_button.request_focus();
+                    }
+                },
+            );
+        });
+
+    egui::Window::new("Options Hint")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-5., -5.])
+        .show(ctx, |ui| {
+            ui.add(egui::Label::new(RichText::new(hint).small()));
+        });
+
+    if changed {
+        settings.volume = volume;
+        settings.window = window;
+        settings.player.turn_speed_slow = turn_speed_slow;
+        settings.player.turn_speed_fast = turn_speed_fast;
+    }
+}
+
+pub(crate) fn save_options_system(settings: Res<BaseSettings>) {
+    if let Err(err) = update_user_settings(json!({
+        "volume": settings.volume,
+        "window": settings.window,
+        "player": {
+            "turn_speed_slow": settings.player.turn_speed_slow,
+            "turn_speed_fast": settings.player.turn_speed_fast,
+        },
+    })) {
+        log::error!("unable to save options: {}", err);
+    }
+}
@@ -40,12 +40,21 @@ pub(crate) fn display_paused_menu_system(
                         *started = false;
                         state.pop().unwrap();
                     }
+                    let options_button = ui.button("Options");
+                    if options_button.clicked() {
+                        *started = false;
+                        state.push(GameState::Options).unwrap();
+                    }
 
                     if !*started {
                         *started = true;
                         continue_button.request_focus();
                     }
-                    hint = "Hit Escape or Enter to continue".to_string();
+                    if options_button.has_focus() {
+                        hint = "Hit Enter to change options".to_string();
+                    } else {
+                        hint = "Hit Escape or Enter to continue".to_string();
+                    }
                 },
             );
         });
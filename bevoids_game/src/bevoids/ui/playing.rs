@@ -4,21 +4,77 @@ use bevy_egui::{
     EguiContext,
 };
 
-use crate::bevoids::highscore::Score;
+use crate::bevoids::{
+    highscore::Score, pickup::PowerUps, player::Lives, profile::Profiles, settings::Settings,
+    shield::Shield,
+};
 
-pub(crate) fn display_playing_ui_system(mut egui_context: ResMut<EguiContext>, score: Res<Score>) {
+pub(crate) fn display_playing_ui_system(
+    mut egui_context: ResMut<EguiContext>,
+    score: Res<Score>,
+    lives: Res<Lives>,
+    shield: Res<Shield>,
+    power_ups: Res<PowerUps>,
+    profiles: Res<Profiles>,
+    settings: Res<Settings>,
+) {
     let ctx = egui_context.ctx_mut();
 
+    // distance to the personal best of the player, once there is one to beat
+    let personal_best = profiles
+        .active()
+        .and_then(|profile| profile.personal_best(&settings.general.difficulty))
+        .filter(|best| u32::from(*best) > 0)
+        .map(|best| {
+            let (best, score) = (u32::from(best), u32::from(*score));
+            if score > best {
+                "New personal best!".to_string()
+            } else {
+                format!("{} to personal best", best - score)
+            }
+        });
     let score = score.to_string();
 
     egui::Window::new("Playing Score")
         .resizable(false)
         .title_bar(false)
         .anchor(egui::Align2::RIGHT_TOP, [-20., 10.])
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                ui.add(egui::Label::new(
+                    RichText::new(score).color(egui::Color32::WHITE),
+                ));
+                if let Some(personal_best) = personal_best {
+                    ui.add(egui::Label::new(
+                        RichText::new(personal_best)
+                            .small()
+                            .color(egui::Color32::LIGHT_BLUE),
+                    ));
+                }
+            });
+        });
+
+    egui::Window::new("Playing Lives")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::LEFT_TOP, [20., 10.])
         .show(ctx, |ui| {
             ui.add(egui::Label::new(
-                RichText::new(score).color(egui::Color32::WHITE),
+                RichText::new(format!("Lives: {}", lives.0)).color(egui::Color32::WHITE),
             ));
+            ui.add(
+                egui::ProgressBar::new(shield.fraction(&settings))
+                    .desired_width(100.)
+                    .text(RichText::new("Shield").small()),
+            );
+            // the timed power-ups, along with the seconds left
+            for (kind, left) in power_ups.iter() {
+                ui.add(egui::Label::new(
+                    RichText::new(format!("{} {:.0}s", kind, left.as_secs_f32().ceil()))
+                        .small()
+                        .color(egui::Color32::GOLD),
+                ));
+            }
         });
 
     egui::Window::new("Playing Hint")
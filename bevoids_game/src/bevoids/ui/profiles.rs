@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2, Color32, Label, RichText},
+    EguiContext,
+};
+
+use crate::bevoids::{
+    profile::{Profile, Profiles},
+    settings::BaseSettings,
+    GameState,
+};
+
+#[derive(Default)]
+pub(crate) struct ProfilesMenu {
+    started: bool,
+    new_name: String,
+}
+
+pub(crate) fn display_profiles_menu_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    mut settings: ResMut<BaseSettings>,
+    mut profiles: ResMut<Profiles>,
+    mut menu: Local<ProfilesMenu>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut hint: String = "".to_string();
+    let active = profiles.active().map(|profile| profile.name.clone());
+    let mut selected = None;
+    let mut removed = None;
+    let mut created = None;
+
+    egui::Window::new("Profiles Menu")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::top_down_justified(egui::Align::Center),
+                |ui| {
+                    ui.add(Label::new(
+                        RichText::new("Players").heading().color(Color32::WHITE),
+                    ));
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
+                    egui::Grid::new("Profiles Grid")
+                        .num_columns(2)
+                        .spacing([20., 8.])
+                        .show(ui, |ui| {
+                            for profile in profiles.iter() {
+                                let is_active = active.as_ref() == Some(&profile.name);
+                                let select_button =
+                                    ui.selectable_label(is_active, profile.name.as_str());
+                                if select_button.clicked() {
+                                    selected = Some(profile.name.clone());
+                                }
+                                let remove_button = ui.button("Remove");
+                                if remove_button.clicked() {
+                                    removed = Some(profile.name.clone());
+                                }
+
+                                if select_button.has_focus() {
+                                    hint = format!("Hit Enter to play as {}", profile.name);
+                                } else if remove_button.has_focus() {
+                                    hint = format!("Hit Enter to remove {}", profile.name);
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                    if profiles.iter().next().is_none() {
+                        ui.add(Label::new(
+                            RichText::new("No players yet").color(Color32::LIGHT_BLUE),
+                        ));
+                    }
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let name = menu.new_name.trim().to_string();
+                    let taken = profiles.iter().any(|profile| profile.name == name);
+                    ui.horizontal(|ui| {
+                        let name_box = ui.add(
+                            egui::TextEdit::singleline(&mut menu.new_name)
+                                .hint_text("Name of a new player"),
+                        );
+                        let create_button =
+                            ui.add_enabled(name.len() >= 3 && !taken, egui::Button::new("Create"));
+                        if create_button.clicked() {
+                            created = Some(name.clone());
+                        }
+                        if name_box.has_focus() || create_button.has_focus() {
+                            hint = if taken {
+                                format!("{} is already a player", name)
+                            } else {
+                                "At least 3 characters required".to_string()
+                            };
+                        }
+                    });
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let back_button = ui.button("Back");
+                    if back_button.clicked() {
+                        menu.started = false;
+                        state.pop().unwrap();
+                    }
+
+                    if back_button.has_focus() {
+                        hint = "Hit Escape or Enter to go back".to_string();
+                    } else if !menu.started {
+                        menu.started = true;
+                        back_button.request_focus();
+                    }
+                },
+            );
+        });
+
+    egui::Window::new("Profiles Hint")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-5., -5.])
+        .show(ctx, |ui| {
+            ui.add(egui::Label::new(RichText::new(hint).small()));
+        });
+
+    if let Some(name) = created {
+        // a new player starts out with the preferences in effect
+        if profiles.create(Profile::new(name, &settings)) {
+            menu.new_name.clear();
+            profiles.save();
+        }
+    }
+    if let Some(name) = selected {
+        if let Some(profile) = profiles.select(&name) {
+            profile.apply_to(&mut settings);
+            profiles.save();
+        }
+    }
+    if let Some(name) = removed {
+        profiles.remove(&name);
+        profiles.save();
+    }
+}
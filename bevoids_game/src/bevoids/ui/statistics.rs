@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2, Color32, Label, RichText},
+    EguiContext,
+};
+use std::time::Duration;
+
+use crate::bevoids::{stats::LifetimeStats, GameState};
+
+pub(crate) fn display_statistics_menu_system(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    stats: Res<LifetimeStats>,
+    mut started: Local<bool>,
+) {
+    let ctx = egui_context.ctx_mut();
+    let mut hint: String = "".to_string();
+
+    let destroyed = &stats.asteroids_destroyed;
+    let rows = [
+        ("Games played", stats.games_played.to_string()),
+        ("Total play time", format_duration(stats.play_time)),
+        ("Longest survival", format_duration(stats.longest_survival)),
+        ("Deaths", stats.deaths.to_string()),
+        ("Lasers fired", stats.lasers_fired.to_string()),
+        ("Accuracy", format!("{:.0}%", stats.accuracy() * 100.)),
+        ("Large asteroids destroyed", destroyed.large.to_string()),
+        ("Medium asteroids destroyed", destroyed.medium.to_string()),
+        ("Small asteroids destroyed", destroyed.small.to_string()),
+    ];
+
+    egui::Window::new("Statistics Menu")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .show(ctx, |ui| {
+            ui.with_layout(
+                egui::Layout::top_down_justified(egui::Align::Center),
+                |ui| {
+                    ui.add(Label::new(
+                        RichText::new("Statistics").heading().color(Color32::WHITE),
+                    ));
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+
+                    egui::Grid::new("Statistics Grid")
+                        .num_columns(2)
+                        .spacing([40., 4.])
+                        .show(ui, |ui| {
+                            for (name, value) in rows {
+                                ui.add(Label::new(RichText::new(name).color(Color32::LIGHT_BLUE)));
+                                ui.add(Label::new(
+                                    RichText::new(value).monospace().color(Color32::WHITE),
+                                ));
+                                ui.end_row();
+                            }
+                        });
+
+                    ui.add(egui::Separator::default().horizontal().spacing(20.));
+                    let back_button = ui.button("Back");
+                    if back_button.clicked() {
+                        *started = false;
+                        state.pop().unwrap();
+                    }
+
+                    if back_button.has_focus() {
+                        hint = "Hit Escape or Enter to go back".to_string();
+                    } else if !*started {
+                        *started = true;
+                        back_button.request_focus();
+                    }
+                },
+            );
+        });
+
+    egui::Window::new("Statistics Hint")
+        .resizable(false)
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-5., -5.])
+        .show(ctx, |ui| {
+            ui.add(egui::Label::new(RichText::new(hint).small()));
+        });
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -2,38 +2,104 @@
 
 use bevoids_assets::{SpriteAssetIo, AsteroidAssetIo, BackgroundAssetIo, SoundAssetIo};
 use bevy::{
-    log,
+    log::{self, LogSettings},
     prelude::*,
     render::camera::{DepthCalculation, OrthographicProjection, ScalingMode},
 };
 use bevy_effects::{animation::TextureAtlasMap, sound::SoundEffectSettings};
 use bevy_embasset::*;
+use std::{env, process};
 
+mod args;
 mod bevoids;
 mod bounds;
 
-use crate::bevoids::Bevoids;
+use crate::{
+    args::{Args, Command, USAGE},
+    bevoids::{export_highscores, import_highscores, Bevoids},
+};
 
-use self::bevoids::settings::Settings;
+use self::bevoids::settings::{BaseSettings, SettingsLoader, SettingsWatchPlugin};
 
 fn main() {
-    let settings: Settings = serde_json::from_slice(include_bytes!("settings.json"))
-        .expect("unable to parse settings file");
+    let args = match Args::parse(env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let settings_loader = SettingsLoader::new(args.settings);
+    let mut settings = match settings_loader.load() {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("error loading settings: {}", err);
+            process::exit(1);
+        }
+    };
+    if let Some((width, height)) = args.window_size {
+        settings.window.width = width;
+        settings.window.height = height;
+    }
+    let effective_settings = settings
+        .effective()
+        .expect("difficulties verified while loading");
+    if args.print_default_settings {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&effective_settings).expect("settings are serializable")
+        );
+        return;
+    }
 
-    App::new()
-        .insert_resource(ClearColor(Color::BLACK))
+    if let Some(command) = args.command {
+        let result = match &command {
+            Command::ExportHighscores(path) => export_highscores(args.highscores, path)
+                .map(|count| format!("exported {} highscores to {}", count, path.display())),
+            Command::ImportHighscores(path) => {
+                import_highscores(args.highscores, &effective_settings, path).map(|count| {
+                    format!("imported {} new highscores from {}", count, path.display())
+                })
+            }
+        };
+        match result {
+            Ok(message) => println!("{}", message),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let mut app = App::new();
+    if let Some(filter) = args.log_filter {
+        app.insert_resource(LogSettings {
+            filter,
+            ..LogSettings::default()
+        });
+    }
+    app.insert_resource(ClearColor(Color::BLACK))
         .insert_resource(Msaa { samples: 4 })
         .init_resource::<TextureAtlasMap>() // TODO: auto add if not added by user
         .init_resource::<SoundEffectSettings>() // TODO: auto add if not added by user
         .insert_resource(WindowDescriptor {
             vsync: true,
-            resizable: false,
+            resizable: true,
             width: settings.window.width as f32,
             height: settings.window.height as f32,
+            mode: settings.window.mode.into(),
             title: module_path!().into(),
             ..WindowDescriptor::default()
         })
-        .insert_resource(settings)
+        .insert_resource(BaseSettings(settings))
+        .insert_resource(effective_settings)
+        .insert_resource(settings_loader)
         //
         .add_embasset_plugin(|io| {
             io.add_handler(SpriteAssetIo::new().into());
@@ -43,9 +109,17 @@ fn main() {
         })
         .add_startup_system(initialize_camera.system())
         //
-        .add_plugin(Bevoids::default())
-        //
-        .run();
+        .add_plugin(Bevoids {
+            start_state: args.start_state,
+            seed: args.seed,
+            highscores: args.highscores,
+        });
+
+    if args.watch_settings {
+        app.add_plugin(SettingsWatchPlugin);
+    }
+
+    app.run();
 }
 
 fn initialize_camera(mut commands: Commands) {
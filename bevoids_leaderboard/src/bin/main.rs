@@ -0,0 +1,54 @@
+use bevoids_leaderboard::{Leaderboard, Server};
+use std::{env, path::PathBuf, process};
+
+const USAGE: &str = "\
+Usage: bevoids-leaderboard [OPTIONS]
+
+Options:
+      --address <ADDR>  Address to listen on [default: 127.0.0.1:8080]
+      --capacity <N>    Number of highscores kept on each board [default: 100]
+      --file <FILE>     Keep the highscores in FILE, instead of only in memory
+  -h, --help            Print this help, then exit";
+
+fn main() {
+    let mut address = "127.0.0.1:8080".to_string();
+    let mut capacity = 100;
+    let mut file = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| exit(&format!("`{}` requires a value", arg)))
+        };
+        match arg.as_str() {
+            "--address" => address = value(),
+            "--capacity" => {
+                capacity = value()
+                    .parse()
+                    .unwrap_or_else(|_| exit("`--capacity` expects a number"))
+            }
+            "--file" => file = Some(PathBuf::from(value())),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit(&format!("unknown argument `{}`", arg)),
+        }
+    }
+
+    let leaderboard = match file {
+        Some(file) => Leaderboard::with_file(capacity, file)
+            .unwrap_or_else(|err| exit(&format!("unable to read highscores: {}", err))),
+        None => Leaderboard::new(capacity),
+    };
+    let server = Server::bind(&address, leaderboard)
+        .unwrap_or_else(|err| exit(&format!("unable to listen on {}: {}", address, err)));
+    println!("leaderboard listening on http://{}", server.address());
+    server.run();
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{cmp::Reverse, collections::BTreeMap, fs, io, path::PathBuf};
+
+/// A single highscore on the leaderboard.
+///
+/// Only the score, name, and the board it belongs to are of interest to the server - anything
+/// else the game sends along is kept as is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub score: u32,
+    pub name: String,
+    #[serde(default)]
+    pub mode: String,
+    #[serde(default)]
+    pub difficulty: String,
+    #[serde(flatten)]
+    pub rest: Map<String, Value>,
+}
+
+/// Identifies a board: the game mode and difficulty.
+pub type BoardKey = (String, String);
+
+impl Entry {
+    pub fn board(&self) -> BoardKey {
+        (self.mode.clone(), self.difficulty.clone())
+    }
+}
+
+/// The best highscores, best first, on separate boards for each game mode and difficulty.
+#[derive(Debug)]
+pub struct Leaderboard {
+    boards: BTreeMap<BoardKey, Vec<Entry>>,
+    capacity: usize,
+    file: Option<PathBuf>,
+}
+
+impl Leaderboard {
+    /// A leaderboard keeping `capacity` entries on each board.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            boards: BTreeMap::new(),
+            capacity,
+            file: None,
+        }
+    }
+
+    /// A leaderboard kept in `file`, starting out with the entries already there.
+    pub fn with_file(capacity: usize, file: PathBuf) -> io::Result<Self> {
+        let entries: Vec<Entry> = match fs::read_to_string(&file) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+
+        let mut boards = BTreeMap::<_, Vec<_>>::new();
+        for entry in entries {
+            boards.entry(entry.board()).or_default().push(entry);
+        }
+        for entries in boards.values_mut() {
+            entries.sort_by_key(|entry| Reverse(entry.score));
+            entries.truncate(capacity);
+        }
+        Ok(Self {
+            boards,
+            capacity,
+            file: Some(file),
+        })
+    }
+
+    /// Adds `entry` to its board, returning its rank if it made it onto the board.
+    pub fn submit(&mut self, entry: Entry) -> io::Result<Option<usize>> {
+        let entries = self.boards.entry(entry.board()).or_default();
+        let rank = entries
+            .iter()
+            .position(|e| entry.score >= e.score)
+            .unwrap_or(entries.len());
+        if rank >= self.capacity {
+            return Ok(None);
+        }
+
+        entries.insert(rank, entry);
+        entries.truncate(self.capacity);
+        self.save()?;
+        Ok(Some(rank))
+    }
+
+    /// Writes the entries to a temporary file, then moves it in place of the old one - a failed
+    /// write never leaves a half written leaderboard behind.
+    fn save(&self) -> io::Result<()> {
+        if let Some(file) = &self.file {
+            let entries: Vec<_> = self.boards.values().flatten().collect();
+            let mut temp_file = file.clone().into_os_string();
+            temp_file.push(".tmp");
+            fs::write(&temp_file, serde_json::to_string_pretty(&entries)?)?;
+            if let Err(err) = fs::rename(&temp_file, file) {
+                fs::remove_file(&temp_file).ok();
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// The `count` best entries on the board `key`.
+    pub fn top(&self, key: &BoardKey, count: usize) -> &[Entry] {
+        self.boards
+            .get(key)
+            .map_or(&[], |entries| &entries[..count.min(entries.len())])
+    }
+
+    /// Rank of the best entry by `name` on the board `key`.
+    pub fn rank(&self, key: &BoardKey, name: &str) -> Option<usize> {
+        self.boards
+            .get(key)
+            .and_then(|entries| entries.iter().position(|e| e.name == name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(score: u32, name: &str, difficulty: &str) -> Entry {
+        Entry {
+            score,
+            name: name.to_string(),
+            mode: "Classic".to_string(),
+            difficulty: difficulty.to_string(),
+            rest: Map::new(),
+        }
+    }
+
+    fn board(difficulty: &str) -> BoardKey {
+        ("Classic".to_string(), difficulty.to_string())
+    }
+
+    #[test]
+    fn entries_are_ranked_best_first() {
+        let mut leaderboard = Leaderboard::new(3);
+        assert_eq!(
+            leaderboard.submit(entry(10, "abc", "Normal")).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            leaderboard.submit(entry(30, "def", "Normal")).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            leaderboard.submit(entry(20, "ghi", "Normal")).unwrap(),
+            Some(1)
+        );
+        assert_eq!(leaderboard.submit(entry(5, "jkl", "Normal")).unwrap(), None);
+
+        let names: Vec<_> = leaderboard
+            .top(&board("Normal"), 5)
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, ["def", "ghi", "abc"]);
+        assert_eq!(leaderboard.rank(&board("Normal"), "abc"), Some(2));
+        assert_eq!(leaderboard.rank(&board("Normal"), "jkl"), None);
+    }
+
+    #[test]
+    fn boards_are_separate() {
+        let mut leaderboard = Leaderboard::new(3);
+        leaderboard.submit(entry(10, "abc", "Normal")).unwrap();
+        assert_eq!(
+            leaderboard.submit(entry(5, "def", "Hard")).unwrap(),
+            Some(0)
+        );
+
+        assert_eq!(leaderboard.top(&board("Hard"), 5).len(), 1);
+        assert_eq!(leaderboard.rank(&board("Hard"), "abc"), None);
+        assert!(leaderboard.top(&board("Easy"), 5).is_empty());
+    }
+
+    #[test]
+    fn unknown_fields_are_kept() {
+        let entry: Entry = serde_json::from_str(
+            r#"{ "score": 1, "name": "abc", "time": "2022-01-01T00:00:00Z" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&entry).unwrap()["time"],
+            Value::from("2022-01-01T00:00:00Z")
+        );
+    }
+}
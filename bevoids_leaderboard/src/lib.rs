@@ -0,0 +1,18 @@
+//! A small leaderboard server, keeping the highscores of bevoids players in one place.
+//!
+//! The REST interface:
+//!
+//! - `POST /scores` with a highscore as body, answers `{ "rank": <rank or null> }`.
+//! - `GET /scores?mode=<mode>&difficulty=<difficulty>&count=<n>` answers the best `n` highscores
+//!   of a board, best first.
+//! - `GET /rank?mode=<mode>&difficulty=<difficulty>&name=<name>` answers
+//!   `{ "rank": <rank or null> }` of the best highscore by `name` on a board.
+//!
+//! Highscores are kept on separate boards for each game mode and difficulty, as given by the
+//! `mode` and `difficulty` of each highscore. Ranks start from 0, being the best.
+
+pub mod leaderboard;
+pub mod server;
+
+pub use leaderboard::{Entry, Leaderboard};
+pub use server::Server;
@@ -0,0 +1,148 @@
+use serde_json::json;
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    thread::{self, JoinHandle},
+};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{leaderboard::BoardKey, Entry, Leaderboard};
+
+/// Serves a [Leaderboard] over HTTP.
+pub struct Server {
+    http: tiny_http::Server,
+    leaderboard: Leaderboard,
+}
+
+impl Server {
+    pub fn bind(address: impl ToSocketAddrs, leaderboard: Leaderboard) -> io::Result<Self> {
+        let http = tiny_http::Server::http(address)
+            .map_err(|err| io::Error::new(io::ErrorKind::AddrNotAvailable, err))?;
+        Ok(Self { http, leaderboard })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.http
+            .server_addr()
+            .to_ip()
+            .expect("bound to an ip address")
+    }
+
+    /// Handles requests until the process ends.
+    pub fn run(mut self) {
+        while let Ok(request) = self.http.recv() {
+            self.handle(request);
+        }
+    }
+
+    /// Handles requests on a background thread.
+    pub fn spawn(self) -> JoinHandle<()> {
+        thread::spawn(move || self.run())
+    }
+
+    fn handle(&mut self, mut request: Request) {
+        let (path, query) = request
+            .url()
+            .split_once('?')
+            .map_or((request.url(), ""), |(path, query)| (path, query));
+        let response = match (request.method(), path) {
+            (Method::Post, "/scores") => {
+                let mut body = String::new();
+                match request
+                    .as_reader()
+                    .read_to_string(&mut body)
+                    .map_err(|err| err.to_string())
+                    .and_then(|_| {
+                        serde_json::from_str::<Entry>(&body).map_err(|err| err.to_string())
+                    }) {
+                    Ok(entry) => match self.leaderboard.submit(entry) {
+                        Ok(rank) => json_response(200, json!({ "rank": rank })),
+                        Err(err) => error_response(500, &err.to_string()),
+                    },
+                    Err(err) => error_response(400, &err),
+                }
+            }
+            (Method::Get, "/scores") => match query_value(query, "count").map(str::parse) {
+                Some(Ok(count)) => {
+                    json_response(200, json!(self.leaderboard.top(&board_key(query), count)))
+                }
+                _ => error_response(400, "expected a count"),
+            },
+            (Method::Get, "/rank") => match query_value(query, "name") {
+                Some(name) => json_response(
+                    200,
+                    json!({ "rank": self.leaderboard.rank(&board_key(query), &percent_decode(name)) }),
+                ),
+                None => error_response(400, "expected a name"),
+            },
+            _ => error_response(404, "not found"),
+        };
+        request.respond(response).ok();
+    }
+}
+
+fn json_response(status: u16, body: serde_json::Value) -> Response<io::Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"))
+}
+
+fn error_response(status: u16, message: &str) -> Response<io::Cursor<Vec<u8>>> {
+    json_response(status, json!({ "error": message }))
+}
+
+/// The board selected by the `mode` and `difficulty` of the query.
+fn board_key(query: &str) -> BoardKey {
+    let value = |key| query_value(query, key).map_or_else(String::new, percent_decode);
+    (value("mode"), value("difficulty"))
+}
+
+fn query_value<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value)
+}
+
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = iter.by_ref().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => bytes.push(decoded),
+                    None => {
+                        bytes.push(b'%');
+                        bytes.extend(hex);
+                    }
+                }
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_values_are_found() {
+        assert_eq!(query_value("count=5&name=abc", "name"), Some("abc"));
+        assert_eq!(query_value("count=5", "name"), None);
+    }
+
+    #[test]
+    fn names_are_percent_decoded() {
+        assert_eq!(percent_decode("J%C3%B8rn+Doe"), "Jørn Doe");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
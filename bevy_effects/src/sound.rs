@@ -234,7 +234,6 @@ impl<KEY> SetVolSfx<KEY>
 where
     KEY: 'static + Send + Sync + Clone + Into<AssetPath<'static>>,
 {
-    #[allow(dead_code)]
     pub fn new(key: KEY, volume: f32) -> Self {
         Self {
             key,